@@ -34,6 +34,67 @@ fn zuc128_keystream(c: &mut Criterion) {
     group.finish();
 }
 
+fn zuc128_bitsliced_keystream(c: &mut Criterion) {
+    let keys: Vec<[u8; 16]> = (0..64).map(|_| rand::random()).collect();
+    let ivs: Vec<[u8; 16]> = (0..64).map(|_| rand::random()).collect();
+
+    let mut group = c.benchmark_group("zuc128_bitsliced_keystream");
+
+    for &size in &[1000, 10000] {
+        group.throughput(Throughput::Bytes((size * 4 * 64) as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, &s| {
+            let mut zuc = zuc::zuc128::Zuc128BitslicedKeystream::<u64>::new(&keys, &ivs);
+            let mut buffer = vec![0u32; 64];
+            b.iter(|| {
+                for _ in 0..s {
+                    zuc.generate(&mut buffer);
+                }
+                black_box(&buffer);
+            });
+        });
+    }
+    group.finish();
+}
+
+fn init_latency(c: &mut Criterion) {
+    let key: [u8; 16] = rand::random();
+    let iv: [u8; 16] = rand::random();
+    let k256: [u8; 32] = rand::random();
+    let iv256: [u8; 23] = rand::random();
+
+    let mut group = c.benchmark_group("init_latency");
+
+    // init plus N bytes of keystream, as for a single short packet
+    for &size in &[0_usize, 40, 100, 1500] {
+        group.throughput(Throughput::Bytes(size as u64));
+
+        group.bench_with_input(BenchmarkId::new("zuc128", size), &size, |b, &s| {
+            b.iter(|| {
+                let mut zuc = zuc::zuc128::Zuc128Keystream::new(black_box(&key), black_box(&iv));
+                (0..s.div_ceil(4)).fold(0, |acc, _| acc ^ zuc.generate())
+            });
+        });
+
+        group.bench_with_input(BenchmarkId::new("zuc256", size), &size, |b, &s| {
+            b.iter(|| {
+                let mut zuc =
+                    zuc::zuc256::Zuc256Keystream::new(black_box(&k256), black_box(&iv256));
+                (0..s.div_ceil(4)).fold(0, |acc, _| acc ^ zuc.generate())
+            });
+        });
+
+        let mut data = vec![0u8; size];
+        group.bench_with_input(BenchmarkId::new("eea3", size), &size, |b, &s| {
+            b.iter(|| {
+                zuc::eea3::eea3_xor_inplace(black_box(0x1234), 5, 1, &key, &mut data, s * 8);
+                black_box(&data);
+            });
+        });
+    }
+
+    group.finish();
+}
+
 fn eia3_mac(c: &mut Criterion) {
     let mac = |input: &[u8]| -> u32 {
         let count = 0x561e_b2dd;
@@ -63,6 +124,87 @@ fn eia3_mac(c: &mut Criterion) {
     group.finish();
 }
 
+fn eia3_mac_batch(c: &mut Criterion) {
+    let count = 0x561e_b2dd;
+    let bearer = 0x14;
+    let direction = 0;
+    let ik = &hex!("47 05 41 25 56 1e b2 dd a9 40 59 da 05 09 78 50");
+
+    let mut group = c.benchmark_group("eia3_mac_batch");
+
+    for &size in &[40, 100, 300, 1500] {
+        let jobs = 64;
+        group.throughput(criterion::Throughput::Bytes((size * jobs) as u64));
+
+        let inputs: Vec<Vec<u8>> = (0..jobs)
+            .map(|_| (0..size).map(|_| rand::random::<u8>()).collect())
+            .collect();
+
+        group.bench_with_input(BenchmarkId::new("serial", size), &inputs, |b, inputs| {
+            b.iter(|| {
+                for (i, input) in inputs.iter().enumerate() {
+                    let count = count + i as u32;
+                    let length = input.len() * 8;
+                    black_box(zuc::eia3::Eia3Mac::compute(
+                        count, bearer, direction, ik, input, length,
+                    ));
+                }
+            });
+        });
+
+        group.bench_with_input(BenchmarkId::new("batch", size), &inputs, |b, inputs| {
+            b.iter(|| {
+                let mut jobs: Vec<_> = (inputs.iter().enumerate())
+                    .map(|(i, input)| {
+                        let count = count + i as u32;
+                        let length = input.len() * 8;
+                        zuc::eia3::Eia3MacJob::new(count, bearer, direction, ik, input, length)
+                    })
+                    .collect();
+                zuc::eia3::Eia3Mac::compute_batch(&mut jobs);
+                black_box(jobs);
+            });
+        });
+    }
+
+    group.finish();
+}
+
+fn eea3_eia3(c: &mut Criterion) {
+    let (count, bearer, direction) = (0x561e_b2dd, 0x14, 0);
+    let ck = &hex!("17 3d 14 ba 50 03 73 1d 7a 60 04 94 70 f0 0a 29");
+    let ik = &hex!("47 05 41 25 56 1e b2 dd a9 40 59 da 05 09 78 50");
+
+    let mut group = c.benchmark_group("eea3_eia3");
+
+    for &size in &[40, 100, 300, 1500, 10000] {
+        group.throughput(Throughput::Bytes(size as u64));
+
+        let mut data: Vec<u8> = (0..size).map(|_| rand::random::<u8>()).collect();
+        let length = size * 8 - 3;
+
+        group.bench_with_input(BenchmarkId::new("separate", size), &size, |b, _| {
+            b.iter(|| {
+                zuc::eea3::eea3_xor_inplace(count, bearer, direction, ck, &mut data, length);
+                black_box(zuc::eia3::Eia3Mac::compute(
+                    count, bearer, direction, ik, &data, length,
+                ))
+            });
+        });
+
+        group.bench_with_input(BenchmarkId::new("fused", size), &size, |b, _| {
+            b.iter(|| {
+                let order = zuc::eea3::MacOrder::EncryptThenMac;
+                black_box(zuc::eea3::eea3_eia3_inplace(
+                    count, bearer, direction, ck, ik, &mut data, length, order,
+                ))
+            });
+        });
+    }
+
+    group.finish();
+}
+
 fn zuc256_mac(c: &mut Criterion) {
     let mac = |input: &[u8]| -> u128 {
         let length = input.len() * 8 - 15;
@@ -90,5 +232,14 @@ fn zuc256_mac(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, zuc128_keystream, eia3_mac, zuc256_mac);
+criterion_group!(
+    benches,
+    zuc128_keystream,
+    zuc128_bitsliced_keystream,
+    init_latency,
+    eia3_mac,
+    eia3_mac_batch,
+    eea3_eia3,
+    zuc256_mac
+);
 criterion_main!(benches);
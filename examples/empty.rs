@@ -0,0 +1,7 @@
+//! An empty program, the baseline of `examples/size.rs`.
+//!
+//! ```bash
+//! just size
+//! ```
+
+fn main() {}
@@ -0,0 +1,34 @@
+//! Calls the single-stream APIs, so that the code size of the crate can be compared across features.
+//!
+//! ```bash
+//! just size
+//! ```
+
+use std::hint::black_box;
+
+use zuc::eea3::eea3_xor_inplace;
+use zuc::eia3::Eia3Mac;
+use zuc::zuc128::{zuc128_xor_inplace, Zuc128Mac};
+use zuc::zuc256::{Zuc256Keystream, Zuc256Mac};
+
+fn main() {
+    let k128: [u8; 16] = black_box([0x11; 16]);
+    let iv128: [u8; 16] = black_box([0x22; 16]);
+    let k256: [u8; 32] = black_box([0x33; 32]);
+    let iv256: [u8; 23] = black_box([0x44; 23]);
+    let mut data = black_box([0x55; 64]);
+
+    zuc128_xor_inplace(&k128, &iv128, &mut data, 500);
+    eea3_xor_inplace(1, 2, 0, &k128, &mut data, 500);
+
+    let mut zuc = Zuc256Keystream::new(&k256, &iv256);
+    black_box(zuc.generate());
+
+    black_box(Zuc128Mac::compute(&k128, &iv128, &data, 500));
+    black_box(Eia3Mac::compute(1, 2, 0, &k128, &data, 500));
+    black_box(Zuc256Mac::<u32>::compute(&k256, &iv256, &data, 500));
+    black_box(Zuc256Mac::<u64>::compute(&k256, &iv256, &data, 500));
+    black_box(Zuc256Mac::<u128>::compute(&k256, &iv256, &data, 500));
+
+    black_box(&data);
+}
@@ -0,0 +1,871 @@
+use crate::internal::mac::MacTag;
+use crate::zuc128::{Zuc128Mac, Zuc128StreamCipher};
+use crate::zuc256::{Zuc256Aead, Zuc256StreamAead};
+
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::vec;
+use std::vec::Vec;
+
+use aead::stream::{NewStream, Nonce as StreamNonce};
+use aead::{AeadInPlace, KeyInit, Nonce, Tag};
+use cipher::{KeyIvInit, StreamCipher};
+use numeric_cast::TruncatingCast;
+use subtle::ConstantTimeEq;
+use zeroize::Zeroizing;
+
+/// Magic bytes at the start of a container
+pub const MAGIC: [u8; 4] = *b"ZUCC";
+
+/// Version of the container format
+pub const VERSION: u8 = 1;
+
+/// Maximum chunk size in bytes
+pub const MAX_CHUNK_SIZE: u32 = 1 << 24;
+
+/// Byte size of the nonce of a container
+///
+/// It is the base nonce of the chunks, to which the chunk index and the last-chunk flag are appended.
+pub const NONCE_SIZE: usize = 11;
+
+/// byte size of the fixed header fields before the nonce
+const FIXED_LEN: usize = 12;
+
+/// last-chunk flag of the header tag nonce, which no chunk uses
+const HEADER_FLAG: u8 = 0x02;
+
+/// maximum tag size in bytes
+const MAX_TAG_SIZE: usize = 16;
+
+/// Algorithm of a container
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Algorithm {
+    /// [`Zuc128StreamCipher`] then [`Zuc128Mac`], with 32 bits tags
+    Zuc128EncryptThenMac,
+    /// [`Zuc256StreamAead`] with 64 bits tags
+    Zuc256Aead64,
+    /// [`Zuc256StreamAead`] with 128 bits tags
+    Zuc256Aead128,
+}
+
+impl Algorithm {
+    /// Returns the algorithm identifier and the tag size of the encoding
+    fn encode(self) -> (u8, u8) {
+        match self {
+            Algorithm::Zuc128EncryptThenMac => (1, 4),
+            Algorithm::Zuc256Aead64 => (2, 8),
+            Algorithm::Zuc256Aead128 => (2, 16),
+        }
+    }
+
+    /// Parses the algorithm identifier and the tag size
+    fn decode(id: u8, tag_size: u8) -> Result<Self, ContainerError> {
+        match (id, tag_size) {
+            (1, 4) => Ok(Algorithm::Zuc128EncryptThenMac),
+            (2, 8) => Ok(Algorithm::Zuc256Aead64),
+            (2, 16) => Ok(Algorithm::Zuc256Aead128),
+            (1 | 2, _) => Err(ContainerError::InvalidTagSize(tag_size)),
+            _ => Err(ContainerError::UnsupportedAlgorithm(id)),
+        }
+    }
+
+    /// Returns the tag size in bytes
+    #[must_use]
+    pub fn tag_size(self) -> usize {
+        usize::from(self.encode().1)
+    }
+}
+
+/// Error of sealing or opening a container
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ContainerError {
+    /// An I/O error of the reader or the writer
+    Io(io::Error),
+    /// The data does not start with [`MAGIC`]
+    InvalidMagic,
+    /// The format version is not supported
+    UnsupportedVersion(u8),
+    /// The algorithm identifier is not supported
+    UnsupportedAlgorithm(u8),
+    /// The tag size is not valid for the algorithm
+    InvalidTagSize(u8),
+    /// The nonce size is not [`NONCE_SIZE`]
+    InvalidNonceSize(usize),
+    /// The chunk size is zero or greater than [`MAX_CHUNK_SIZE`]
+    InvalidChunkSize(u32),
+    /// The encoded header has a wrong length
+    InvalidHeaderLength,
+    /// The header tag does not match, or the key is wrong
+    HeaderAuthentication,
+    /// The tag of the chunk at the index does not match
+    ///
+    /// Data appended after the last chunk is read as part of it, so it is reported as this error.
+    ChunkAuthentication(u32),
+    /// The container ends before its last chunk
+    Truncated,
+    /// The payload needs more chunks than the chunk index can count
+    TooManyChunks,
+}
+
+impl fmt::Display for ContainerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "container I/O error: {err}"),
+            Self::InvalidMagic => write!(f, "invalid container magic"),
+            Self::UnsupportedVersion(v) => write!(f, "unsupported container version: {v}"),
+            Self::UnsupportedAlgorithm(id) => write!(f, "unsupported container algorithm: {id}"),
+            Self::InvalidTagSize(n) => write!(f, "invalid container tag size: {n}"),
+            Self::InvalidNonceSize(n) => write!(f, "invalid container nonce size: {n}"),
+            Self::InvalidChunkSize(n) => write!(f, "invalid container chunk size: {n}"),
+            Self::InvalidHeaderLength => write!(f, "invalid container header length"),
+            Self::HeaderAuthentication => write!(f, "container header authentication failed"),
+            Self::ChunkAuthentication(i) => write!(f, "container chunk {i} authentication failed"),
+            Self::Truncated => write!(f, "container is truncated"),
+            Self::TooManyChunks => write!(f, "container has too many chunks"),
+        }
+    }
+}
+
+impl std::error::Error for ContainerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ContainerError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// Header of a container
+///
+/// # Format
+/// A container is the encoded header, the header tag, and the chunks. Integers are in big endian.
+///
+/// | offset | size | field                                          |
+/// | ------ | ---- | ---------------------------------------------- |
+/// | 0      | 4    | magic, [`MAGIC`]                               |
+/// | 4      | 1    | version, [`VERSION`]                           |
+/// | 5      | 1    | algorithm: 1 ZUC128, 2 ZUC256                  |
+/// | 6      | 1    | tag size `t`: 4 for ZUC128, 8 or 16 for ZUC256 |
+/// | 7      | 1    | nonce size: [`NONCE_SIZE`]                     |
+/// | 8      | 4    | chunk size `c`, from 1 to [`MAX_CHUNK_SIZE`]   |
+/// | 12     | 11   | nonce                                          |
+/// | 23     | `t`  | header tag                                     |
+///
+/// Each chunk is `c` bytes of ciphertext followed by its `t` bytes tag,
+/// except the last chunk, which has less than `c` bytes of ciphertext, possibly none.
+///
+/// # Construction
+/// The key is 256 bits for every algorithm.
+///
+/// With ZUC256, the container is sealed with [`Zuc256Aead<T>`] of the key,
+/// where `T` is `u64` or `u128` for the tag size.
+/// + The chunk at index `i` is encrypted by [`Zuc256StreamAead<T>`] with the nonce as its base nonce,
+///   that is with the [`Zuc256Aead<T>`] nonce `nonce || be32(i) || last`, without associated data.
+/// + The header tag is the [`Zuc256Aead<T>`] tag of the empty message with the encoded header
+///   as associated data, under the nonce `nonce || be32(0) || 0x02`, which no chunk uses.
+///
+/// With ZUC128, bytes 0 to 15 of the key are the encryption key `KE`
+/// and bytes 16 to 31 are the MAC key `KM`.
+/// + The chunk at index `i` is XOR-ed with the [`Zuc128StreamCipher`] of `KE`,
+///   and its tag is the big endian [`Zuc128Mac`] of `KM` over the ciphertext,
+///   both with the iv `nonce || be32(i) || last`.
+/// + The header tag is the [`Zuc128Mac`] of `KM` over the encoded header,
+///   with the iv `nonce || be32(0) || 0x02`, which no chunk uses.
+///
+/// A 32-bit tag is forged with probability 2<sup>-32</sup> per attempt,
+/// so prefer ZUC256 unless ZUC128 is required.
+///
+/// The nonce is 88 bits, and the chunk index and the last-chunk flag are never supplied by the caller.
+/// It must be unique for each container sealed with the same key.
+/// A random nonce is fine for far fewer than 2<sup>44</sup> containers per key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    /// algorithm
+    algorithm: Algorithm,
+    /// base nonce
+    nonce: [u8; NONCE_SIZE],
+    /// chunk size in bytes
+    chunk_size: u32,
+}
+
+impl Header {
+    /// Creates a header
+    ///
+    /// # Errors
+    /// Returns an error if the nonce size or the chunk size is invalid.
+    pub fn new(
+        algorithm: Algorithm,
+        nonce: &[u8],
+        chunk_size: u32,
+    ) -> Result<Self, ContainerError> {
+        let nonce = nonce
+            .try_into()
+            .map_err(|_| ContainerError::InvalidNonceSize(nonce.len()))?;
+        if chunk_size == 0 || chunk_size > MAX_CHUNK_SIZE {
+            return Err(ContainerError::InvalidChunkSize(chunk_size));
+        }
+        Ok(Self {
+            algorithm,
+            nonce,
+            chunk_size,
+        })
+    }
+
+    /// Returns the algorithm
+    #[must_use]
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// Returns the nonce
+    #[must_use]
+    pub fn nonce(&self) -> &[u8; NONCE_SIZE] {
+        &self.nonce
+    }
+
+    /// Returns the chunk size in bytes
+    #[must_use]
+    pub fn chunk_size(&self) -> u32 {
+        self.chunk_size
+    }
+
+    /// Returns the length of the encoded header, without the header tag
+    #[must_use]
+    pub fn len(&self) -> usize {
+        FIXED_LEN + NONCE_SIZE
+    }
+
+    /// Always returns false, since an encoded header is never empty
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Encodes the header, without the header tag
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let (id, tag_size) = self.algorithm.encode();
+
+        let mut bytes = Vec::with_capacity(self.len());
+        bytes.extend_from_slice(&MAGIC);
+        bytes.extend_from_slice(&[VERSION, id, tag_size, NONCE_SIZE.truncating_cast::<u8>()]);
+        bytes.extend_from_slice(&self.chunk_size.to_be_bytes());
+        bytes.extend_from_slice(&self.nonce);
+        bytes
+    }
+
+    /// Parses an encoded header, without the header tag
+    ///
+    /// The parser is strict: every field must be valid and `bytes` must have the exact length.
+    ///
+    /// # Errors
+    /// Returns the first invalid field.
+    pub fn parse(bytes: &[u8]) -> Result<Self, ContainerError> {
+        let header = Self::parse_fixed(bytes)?;
+        if bytes.len() != header.len() {
+            return Err(ContainerError::InvalidHeaderLength);
+        }
+        Self::new(header.algorithm, &bytes[FIXED_LEN..], header.chunk_size)
+    }
+
+    /// Parses the fixed fields, with a zero nonce
+    fn parse_fixed(bytes: &[u8]) -> Result<Self, ContainerError> {
+        if bytes.len() < FIXED_LEN {
+            return Err(ContainerError::InvalidHeaderLength);
+        }
+        if bytes[..4] != MAGIC {
+            return Err(ContainerError::InvalidMagic);
+        }
+        if bytes[4] != VERSION {
+            return Err(ContainerError::UnsupportedVersion(bytes[4]));
+        }
+        let algorithm = Algorithm::decode(bytes[5], bytes[6])?;
+        let nonce_size = usize::from(bytes[7]);
+        if nonce_size != NONCE_SIZE {
+            return Err(ContainerError::InvalidNonceSize(nonce_size));
+        }
+        let chunk_size = u32::from_be_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]);
+        Self::new(algorithm, &[0; NONCE_SIZE], chunk_size)
+    }
+
+    /// Reads and parses an encoded header, without the header tag
+    ///
+    /// It reads no more than the encoded header.
+    ///
+    /// # Errors
+    /// Returns an error if the reader fails or ends early, or if the header is invalid.
+    pub fn read_from(mut reader: impl Read) -> Result<Self, ContainerError> {
+        let mut bytes = [0; FIXED_LEN + NONCE_SIZE];
+        read_exact(&mut reader, &mut bytes[..FIXED_LEN])?;
+        let _ = Self::parse_fixed(&bytes)?;
+        read_exact(&mut reader, &mut bytes[FIXED_LEN..])?;
+        Self::parse(&bytes)
+    }
+}
+
+/// Cipher of the header tag and the chunks of a container
+trait ContainerCipher {
+    /// Encrypts the chunk at `index` in place and writes its tag
+    fn encrypt_chunk(&self, index: u32, last: bool, chunk: &mut [u8], tag: &mut [u8]);
+
+    /// Verifies the tag of the chunk at `index` and decrypts it in place
+    fn decrypt_chunk(
+        &self,
+        index: u32,
+        last: bool,
+        chunk: &mut [u8],
+        tag: &[u8],
+    ) -> Result<(), ContainerError>;
+
+    /// Writes the header tag
+    fn header_tag(&self, header: &Header, tag: &mut [u8]);
+
+    /// Verifies the header tag
+    fn verify_header(&self, header: &Header, tag: &[u8]) -> Result<(), ContainerError>;
+}
+
+/// ZUC256 container cipher with the tag type `T`
+struct Zuc256Cipher<T: MacTag> {
+    /// header tag cipher
+    aead: Zuc256Aead<T>,
+    /// chunk cipher
+    stream: Zuc256StreamAead<T>,
+}
+
+impl<T: MacTag> Zuc256Cipher<T> {
+    /// Creates the ciphers of the key and the header nonce
+    fn new(key: &[u8; 32], header: &Header) -> Self {
+        let aead = Zuc256Aead::<T>::new(key.into());
+        let nonce = StreamNonce::<Zuc256Aead<T>, Zuc256StreamAead<T>>::from_slice(&header.nonce);
+        let stream = Zuc256StreamAead::from_aead(aead.clone(), nonce);
+        Self { aead, stream }
+    }
+
+    /// Returns the nonce of the header tag
+    fn header_nonce(header: &Header) -> Nonce<Zuc256Aead<T>> {
+        let mut nonce = Nonce::<Zuc256Aead<T>>::default();
+        nonce[..NONCE_SIZE].copy_from_slice(&header.nonce);
+        nonce[15] = HEADER_FLAG;
+        nonce
+    }
+}
+
+impl<T: MacTag> ContainerCipher for Zuc256Cipher<T> {
+    fn encrypt_chunk(&self, index: u32, last: bool, chunk: &mut [u8], tag: &mut [u8]) {
+        let Ok(t) = self.stream.encrypt_chunk(index, last, &[], chunk) else {
+            unreachable!()
+        };
+        tag.copy_from_slice(&t);
+    }
+
+    fn decrypt_chunk(
+        &self,
+        index: u32,
+        last: bool,
+        chunk: &mut [u8],
+        tag: &[u8],
+    ) -> Result<(), ContainerError> {
+        let tag = Tag::<Zuc256Aead<T>>::from_slice(tag);
+        self.stream
+            .decrypt_chunk(index, last, &[], chunk, tag)
+            .map_err(|_| ContainerError::ChunkAuthentication(index))
+    }
+
+    fn header_tag(&self, header: &Header, tag: &mut [u8]) {
+        let nonce = Self::header_nonce(header);
+        let Ok(t) = self
+            .aead
+            .encrypt_in_place_detached(&nonce, &header.to_bytes(), &mut [])
+        else {
+            unreachable!()
+        };
+        tag.copy_from_slice(&t);
+    }
+
+    fn verify_header(&self, header: &Header, tag: &[u8]) -> Result<(), ContainerError> {
+        let nonce = Self::header_nonce(header);
+        let tag = Tag::<Zuc256Aead<T>>::from_slice(tag);
+        self.aead
+            .decrypt_in_place_detached(&nonce, &header.to_bytes(), &mut [], tag)
+            .map_err(|_| ContainerError::HeaderAuthentication)
+    }
+}
+
+/// ZUC128 encrypt-then-MAC container cipher
+struct Zuc128Cipher {
+    /// encryption key
+    ke: Zeroizing<[u8; 16]>,
+    /// MAC key
+    km: Zeroizing<[u8; 16]>,
+    /// base nonce
+    nonce: [u8; NONCE_SIZE],
+}
+
+impl Zuc128Cipher {
+    /// Splits the key and keeps the header nonce
+    fn new(key: &[u8; 32], header: &Header) -> Self {
+        let (ke, km) = key.split_at(16);
+        let mut keys = (Zeroizing::new([0; 16]), Zeroizing::new([0; 16]));
+        keys.0.copy_from_slice(ke);
+        keys.1.copy_from_slice(km);
+        Self {
+            ke: keys.0,
+            km: keys.1,
+            nonce: header.nonce,
+        }
+    }
+
+    /// Returns the iv of the chunk at `index` with the last-chunk `flag`
+    fn iv(&self, index: u32, flag: u8) -> [u8; 16] {
+        let mut iv = [0; 16];
+        iv[..NONCE_SIZE].copy_from_slice(&self.nonce);
+        iv[NONCE_SIZE..15].copy_from_slice(&index.to_be_bytes());
+        iv[15] = flag;
+        iv
+    }
+
+    /// XORs `data` with the keystream of the iv
+    fn xor(&self, iv: &[u8; 16], data: &mut [u8]) {
+        Zuc128StreamCipher::new(&(*self.ke).into(), iv.into()).apply_keystream(data);
+    }
+
+    /// Computes the tag of `msg` with the iv
+    fn tag(&self, iv: &[u8; 16], msg: &[u8]) -> [u8; 4] {
+        Zuc128Mac::compute(&self.km, iv, msg, msg.len() * 8).to_be_bytes()
+    }
+}
+
+impl ContainerCipher for Zuc128Cipher {
+    fn encrypt_chunk(&self, index: u32, last: bool, chunk: &mut [u8], tag: &mut [u8]) {
+        let iv = self.iv(index, u8::from(last));
+        self.xor(&iv, chunk);
+        tag.copy_from_slice(&self.tag(&iv, chunk));
+    }
+
+    fn decrypt_chunk(
+        &self,
+        index: u32,
+        last: bool,
+        chunk: &mut [u8],
+        tag: &[u8],
+    ) -> Result<(), ContainerError> {
+        let iv = self.iv(index, u8::from(last));
+        if !bool::from(self.tag(&iv, chunk).ct_eq(tag)) {
+            return Err(ContainerError::ChunkAuthentication(index));
+        }
+        self.xor(&iv, chunk);
+        Ok(())
+    }
+
+    fn header_tag(&self, header: &Header, tag: &mut [u8]) {
+        let iv = self.iv(0, HEADER_FLAG);
+        tag.copy_from_slice(&self.tag(&iv, &header.to_bytes()));
+    }
+
+    fn verify_header(&self, header: &Header, tag: &[u8]) -> Result<(), ContainerError> {
+        let iv = self.iv(0, HEADER_FLAG);
+        if !bool::from(self.tag(&iv, &header.to_bytes()).ct_eq(tag)) {
+            return Err(ContainerError::HeaderAuthentication);
+        }
+        Ok(())
+    }
+}
+
+/// Reads until `buf` is full or the reader ends, and returns the number of bytes read
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut len = 0;
+    while len < buf.len() {
+        match reader.read(&mut buf[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(len)
+}
+
+/// Reads exactly `buf.len()` bytes
+fn read_exact(reader: &mut impl Read, buf: &mut [u8]) -> Result<(), ContainerError> {
+    if read_full(reader, buf)? < buf.len() {
+        return Err(ContainerError::Truncated);
+    }
+    Ok(())
+}
+
+/// Returns the index of the chunk after `index`
+fn next_index(index: u32) -> Result<u32, ContainerError> {
+    index.checked_add(1).ok_or(ContainerError::TooManyChunks)
+}
+
+/// Encrypts the payload from `reader` into a container written to `writer`
+///
+/// The payload is read and written one chunk at a time. Returns the payload length in bytes.
+///
+/// # Errors
+/// Returns an error if the reader or the writer fails,
+/// or if the payload needs more chunks than the chunk index can count.
+pub fn seal(
+    key: &[u8; 32],
+    header: &Header,
+    reader: impl Read,
+    writer: impl Write,
+) -> Result<u64, ContainerError> {
+    match header.algorithm {
+        Algorithm::Zuc128EncryptThenMac => {
+            let cipher = Zuc128Cipher::new(key, header);
+            seal_with(&cipher, header, reader, writer)
+        }
+        Algorithm::Zuc256Aead64 => {
+            let cipher = Zuc256Cipher::<u64>::new(key, header);
+            seal_with(&cipher, header, reader, writer)
+        }
+        Algorithm::Zuc256Aead128 => {
+            let cipher = Zuc256Cipher::<u128>::new(key, header);
+            seal_with(&cipher, header, reader, writer)
+        }
+    }
+}
+
+/// [`seal`] with the container cipher of the header
+fn seal_with(
+    cipher: &impl ContainerCipher,
+    header: &Header,
+    mut reader: impl Read,
+    mut writer: impl Write,
+) -> Result<u64, ContainerError> {
+    let mut tag = [0; MAX_TAG_SIZE];
+    let tag = &mut tag[..header.algorithm.tag_size()];
+
+    cipher.header_tag(header, tag);
+    writer.write_all(&header.to_bytes())?;
+    writer.write_all(tag)?;
+
+    let chunk_size = header.chunk_size as usize;
+    let mut buf = Zeroizing::new(vec![0; chunk_size]);
+    let mut total = 0;
+    let mut index = 0;
+    loop {
+        let len = read_full(&mut reader, &mut buf)?;
+        let last = len < chunk_size;
+
+        let chunk = &mut buf[..len];
+        cipher.encrypt_chunk(index, last, chunk, tag);
+        writer.write_all(chunk)?;
+        writer.write_all(tag)?;
+
+        total += len as u64;
+        if last {
+            break;
+        }
+        index = next_index(index)?;
+    }
+    writer.flush()?;
+    Ok(total)
+}
+
+/// Decrypts a container from `reader` and writes the payload to `writer`
+///
+/// Each chunk is verified before its plaintext is written.
+/// If an error is returned after some chunks have been written, the output must be discarded.
+/// Returns the header of the container.
+///
+/// # Errors
+/// Returns an error if the header is invalid or not authentic,
+/// if a chunk is not authentic, if the container is truncated,
+/// or if the reader or the writer fails.
+pub fn open(
+    key: &[u8; 32],
+    mut reader: impl Read,
+    writer: impl Write,
+) -> Result<Header, ContainerError> {
+    let header = Header::read_from(&mut reader)?;
+    match header.algorithm {
+        Algorithm::Zuc128EncryptThenMac => {
+            let cipher = Zuc128Cipher::new(key, &header);
+            open_with(&cipher, &header, reader, writer)?;
+        }
+        Algorithm::Zuc256Aead64 => {
+            let cipher = Zuc256Cipher::<u64>::new(key, &header);
+            open_with(&cipher, &header, reader, writer)?;
+        }
+        Algorithm::Zuc256Aead128 => {
+            let cipher = Zuc256Cipher::<u128>::new(key, &header);
+            open_with(&cipher, &header, reader, writer)?;
+        }
+    }
+    Ok(header)
+}
+
+/// [`open`] with the container cipher of the header, after the header
+fn open_with(
+    cipher: &impl ContainerCipher,
+    header: &Header,
+    mut reader: impl Read,
+    mut writer: impl Write,
+) -> Result<(), ContainerError> {
+    let tag_size = header.algorithm.tag_size();
+
+    let mut tag = [0; MAX_TAG_SIZE];
+    read_exact(&mut reader, &mut tag[..tag_size])?;
+    cipher.verify_header(header, &tag[..tag_size])?;
+
+    let chunk_size = header.chunk_size as usize;
+    let mut buf = Zeroizing::new(vec![0; chunk_size + tag_size]);
+    let mut index = 0;
+    loop {
+        let len = read_full(&mut reader, &mut buf)?;
+        if len < tag_size {
+            return Err(ContainerError::Truncated);
+        }
+        let (chunk, tag) = buf[..len].split_at_mut(len - tag_size);
+        let last = chunk.len() < chunk_size;
+
+        cipher.decrypt_chunk(index, last, chunk, tag)?;
+        writer.write_all(chunk)?;
+
+        if last {
+            break;
+        }
+        index = next_index(index)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALGORITHMS: [Algorithm; 3] = [
+        Algorithm::Zuc128EncryptThenMac,
+        Algorithm::Zuc256Aead64,
+        Algorithm::Zuc256Aead128,
+    ];
+
+    const KEY: [u8; 32] = [0x3b; 32];
+
+    fn payload(len: usize) -> Vec<u8> {
+        (0..len)
+            .map(|i| (i * 37 + 11).truncating_cast::<u8>())
+            .collect()
+    }
+
+    fn header(algorithm: Algorithm, chunk_size: u32) -> Header {
+        Header::new(algorithm, &[0x6c; NONCE_SIZE], chunk_size).unwrap()
+    }
+
+    fn sealed(algorithm: Algorithm, chunk_size: u32, len: usize) -> Vec<u8> {
+        let mut out = Vec::new();
+        let header = header(algorithm, chunk_size);
+        let n = seal(&KEY, &header, &payload(len)[..], &mut out).unwrap();
+        assert_eq!(n, len as u64);
+        out
+    }
+
+    fn open_err(container: &[u8]) -> ContainerError {
+        open(&KEY, container, io::sink()).unwrap_err()
+    }
+
+    #[test]
+    fn roundtrip() {
+        for algorithm in ALGORITHMS {
+            for len in [0, 1, 63, 64, 65, 128, 200] {
+                let container = sealed(algorithm, 64, len);
+
+                let tag_size = algorithm.tag_size();
+                let chunks = len / 64 + 1;
+                let header_len = FIXED_LEN + NONCE_SIZE + tag_size;
+                assert_eq!(container.len(), header_len + len + chunks * tag_size);
+
+                let mut out = Vec::new();
+                let header = open(&KEY, &container[..], &mut out).unwrap();
+                assert_eq!(header, self::header(algorithm, 64));
+                assert_eq!(out, payload(len));
+            }
+        }
+    }
+
+    #[test]
+    fn construction() {
+        use crate::zuc256::Zuc256Aead128;
+
+        let container = sealed(Algorithm::Zuc256Aead128, 16, 20);
+        let header = header(Algorithm::Zuc256Aead128, 16);
+        let encoded = header.to_bytes();
+        let (head, chunks) = container.split_at(encoded.len() + 16);
+        assert_eq!(&head[..encoded.len()], encoded);
+
+        let aead = Zuc256Aead128::new(&KEY.into());
+        let mut nonce = [0x6c; 16];
+        nonce[11..].copy_from_slice(&[0, 0, 0, 0, HEADER_FLAG]);
+        let tag = aead
+            .encrypt_in_place_detached(&nonce.into(), &encoded, &mut [])
+            .unwrap();
+        assert_eq!(&head[encoded.len()..], &tag[..]);
+
+        let stream = Zuc256StreamAead::from_aead(aead, &[0x6c; NONCE_SIZE].into());
+        let msg = payload(20);
+        for (i, (chunk, sealed)) in (0..).zip(msg.chunks(16).zip(chunks.chunks(32))) {
+            let mut buf = chunk.to_vec();
+            let tag = stream.encrypt_chunk(i, i == 1, &[], &mut buf).unwrap();
+            assert_eq!(sealed, [buf.as_slice(), &tag].concat());
+        }
+    }
+
+    #[test]
+    fn construction_zuc128() {
+        let container = sealed(Algorithm::Zuc128EncryptThenMac, 16, 20);
+        let header = header(Algorithm::Zuc128EncryptThenMac, 16);
+        let encoded = header.to_bytes();
+        let (head, chunks) = container.split_at(encoded.len() + 4);
+        assert_eq!(&head[..encoded.len()], encoded);
+
+        let (ke, km) = ([0x3b; 16], [0x3b; 16]);
+        let iv = |index: u32, flag: u8| {
+            let mut iv = [0x6c; 16];
+            iv[11..15].copy_from_slice(&index.to_be_bytes());
+            iv[15] = flag;
+            iv
+        };
+
+        let tag = Zuc128Mac::compute(&km, &iv(0, HEADER_FLAG), &encoded, encoded.len() * 8);
+        assert_eq!(&head[encoded.len()..], tag.to_be_bytes());
+
+        let msg = payload(20);
+        for (i, (chunk, sealed)) in (0..).zip(msg.chunks(16).zip(chunks.chunks(20))) {
+            let iv = iv(i, u8::from(i == 1));
+            let mut buf = chunk.to_vec();
+            Zuc128StreamCipher::new(&ke.into(), &iv.into()).apply_keystream(&mut buf);
+            let tag = Zuc128Mac::compute(&km, &iv, &buf, buf.len() * 8);
+            assert_eq!(sealed, [buf.as_slice(), &tag.to_be_bytes()].concat());
+        }
+    }
+
+    #[test]
+    fn parse() {
+        let header = header(Algorithm::Zuc256Aead64, 4096);
+        let bytes = header.to_bytes();
+        assert_eq!(bytes.len(), header.len());
+        assert_eq!(&bytes[..12], b"ZUCC\x01\x02\x08\x0b\x00\x00\x10\x00");
+        assert_eq!(Header::parse(&bytes).unwrap(), header);
+        assert_eq!(Header::read_from(&bytes[..]).unwrap(), header);
+
+        let err = |i: usize, x: u8| {
+            let mut bytes = bytes.clone();
+            bytes[i] = x;
+            Header::parse(&bytes).unwrap_err()
+        };
+        assert!(matches!(err(0, b'X'), ContainerError::InvalidMagic));
+        assert!(matches!(err(4, 2), ContainerError::UnsupportedVersion(2)));
+        assert!(matches!(err(5, 0), ContainerError::UnsupportedAlgorithm(0)));
+        assert!(matches!(err(5, 1), ContainerError::InvalidTagSize(8)));
+        assert!(matches!(err(5, 3), ContainerError::UnsupportedAlgorithm(3)));
+        assert!(matches!(err(6, 4), ContainerError::InvalidTagSize(4)));
+        assert!(matches!(err(7, 16), ContainerError::InvalidNonceSize(16)));
+        assert!(matches!(err(10, 0), ContainerError::InvalidChunkSize(0)));
+        assert!(matches!(err(8, 1), ContainerError::InvalidChunkSize(_)));
+
+        assert!(matches!(
+            Header::parse(&bytes[..bytes.len() - 1]),
+            Err(ContainerError::InvalidHeaderLength)
+        ));
+        assert!(matches!(
+            Header::parse(&[bytes.as_slice(), &[0]].concat()),
+            Err(ContainerError::InvalidHeaderLength)
+        ));
+        assert!(matches!(
+            Header::read_from(&bytes[..20]),
+            Err(ContainerError::Truncated)
+        ));
+
+        let header = self::header(Algorithm::Zuc128EncryptThenMac, 4096);
+        let bytes = header.to_bytes();
+        assert_eq!(&bytes[..12], b"ZUCC\x01\x01\x04\x0b\x00\x00\x10\x00");
+        assert_eq!(Header::parse(&bytes).unwrap(), header);
+
+        assert!(matches!(
+            Header::new(Algorithm::Zuc256Aead64, &[0; 16], 1),
+            Err(ContainerError::InvalidNonceSize(16))
+        ));
+    }
+
+    #[test]
+    fn tamper() {
+        for algorithm in ALGORITHMS {
+            let container = sealed(algorithm, 16, 40);
+            let tag_size = algorithm.tag_size();
+            let header_len = FIXED_LEN + NONCE_SIZE + tag_size;
+            let chunk_len = 16 + tag_size;
+
+            // header fields, nonce and tag
+            for i in [11, 20, header_len - 1] {
+                let mut forged = container.clone();
+                forged[i] ^= 0x01;
+                let err = open_err(&forged);
+                assert!(matches!(err, ContainerError::HeaderAuthentication));
+            }
+
+            // chunk ciphertext and tags
+            for (i, index) in [
+                (0, 0),
+                (chunk_len - 1, 0),
+                (chunk_len, 1),
+                (2 * chunk_len + 3, 2),
+            ] {
+                let mut forged = container.clone();
+                forged[header_len + i] ^= 0x80;
+                let err = open_err(&forged);
+                assert!(matches!(err, ContainerError::ChunkAuthentication(x) if x == index));
+            }
+
+            // reordered chunks
+            let mut forged = container.clone();
+            let body = &mut forged[header_len..header_len + 2 * chunk_len];
+            body.rotate_left(chunk_len);
+            let err = open_err(&forged);
+            assert!(matches!(err, ContainerError::ChunkAuthentication(0)));
+
+            // wrong key
+            let err = open(&[0x3c; 32], &container[..], io::sink()).unwrap_err();
+            assert!(matches!(err, ContainerError::HeaderAuthentication));
+        }
+    }
+
+    #[test]
+    fn truncate() {
+        let algorithm = Algorithm::Zuc256Aead128;
+        let container = sealed(algorithm, 16, 32);
+        let header_len = FIXED_LEN + NONCE_SIZE + 16;
+        let chunk_len = 16 + 16;
+
+        // at a chunk boundary, before the empty last chunk
+        let err = open_err(&container[..header_len + 2 * chunk_len]);
+        assert!(matches!(err, ContainerError::Truncated));
+
+        // inside a chunk
+        let err = open_err(&container[..header_len + chunk_len + 20]);
+        assert!(matches!(err, ContainerError::ChunkAuthentication(1)));
+
+        // inside the header tag
+        let err = open_err(&container[..header_len - 1]);
+        assert!(matches!(err, ContainerError::Truncated));
+
+        // trailing data
+        let err = open_err(&[container.as_slice(), &[0]].concat());
+        assert!(matches!(err, ContainerError::ChunkAuthentication(2)));
+
+        assert!(matches!(
+            next_index(u32::MAX),
+            Err(ContainerError::TooManyChunks)
+        ));
+    }
+}
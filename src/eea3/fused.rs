@@ -0,0 +1,161 @@
+use super::Eea3Keystream;
+
+use crate::eia3::Eia3Mac;
+use crate::internal::keystream::{xor_word, ZucKeystream};
+use crate::internal::mac::{MacCore, MacProfile};
+use crate::internal::stream_cipher::xor_inplace;
+use crate::internal::zuc::Zuc;
+use crate::zuc128::Zuc128Keystream;
+
+use stdx::slice::SliceExt as _;
+use zeroize::Zeroizing;
+
+/// Number of words processed between the keystream generations of [`eea3_eia3_inplace`]
+const FUSED_BLOCK: usize = 16;
+
+/// Order of ciphering and integrity protection in [`eea3_eia3_inplace`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MacOrder {
+    /// The MAC is computed over the input bitstream, before it is ciphered.
+    ///
+    /// Use it to decrypt with [`MacOrder::EncryptThenMac`], or to encrypt MAC-then-encrypt.
+    MacThenEncrypt,
+    /// The MAC is computed over the output bitstream, after it is ciphered.
+    ///
+    /// Use it to decrypt with [`MacOrder::MacThenEncrypt`], or to encrypt encrypt-then-MAC.
+    EncryptThenMac,
+}
+
+/// 128-EEA3 and 128-EIA3 in a single pass
+/// ([EEA3-EIA3-specification](https://www.gsma.com/solutions-and-impact/technologies/security/wp-content/uploads/2019/05/EEA3_EIA3_specification_v1_8.pdf))
+///
+/// The two keystream generators are initialized and advanced in lockstep,
+/// and each block of the bitstream is ciphered and fed into the MAC while it is in cache.
+/// The result is identical to [`eea3_xor_inplace`](super::eea3_xor_inplace)
+/// and [`Eia3Mac::compute`] in the given `order`.
+///
+/// ## Input
+/// | name      | size     | description                           |
+/// | --------- | -------- | ------------------------------------- |
+/// | count     | 32 bits  | counter                               |
+/// | bearer    | 5 bits   | carrier layer identification          |
+/// | direction | 1 bit    | transmission direction identification |
+/// | ck        | 128 bits | confidentiality key                   |
+/// | ik        | 128 bits | integrity key                         |
+/// | data      | -        | the bitstream                         |
+/// | bitlen    | -        | bit length of the bitstream           |
+/// | order     | -        | order of ciphering and MAC            |
+///
+/// ## Output
+/// 32 bits MAC (Message Authentication Code)
+///
+/// # Panics
+/// + Panics if `bitlen` is greater than the bit length of `data`.
+#[allow(clippy::too_many_arguments)]
+#[must_use]
+pub fn eea3_eia3_inplace(
+    count: u32,
+    bearer: u8,
+    direction: u8,
+    ck: &[u8; 16],
+    ik: &[u8; 16],
+    data: &mut [u8],
+    bitlen: usize,
+    order: MacOrder,
+) -> u32 {
+    assert!(bitlen <= data.len() * 8);
+
+    let mut cipher = Zuc128Keystream::load(ck, &Eea3Keystream::iv(count, bearer, direction));
+    let mut integrity = Zuc128Keystream::load(ik, &Eia3Mac::iv(count, bearer, direction));
+    Zuc::init_lanes(&mut [&mut cipher, &mut integrity]);
+
+    let mut cipher = Zuc128Keystream::from_core(cipher);
+    let integrity = Zuc128Keystream::from_core(integrity);
+    let mut mac = MacCore::<_, u32>::new(integrity, MacProfile::Zuc128);
+
+    // the whole words of the bitstream are processed in lockstep
+    let words = bitlen / 32;
+    let (body, rest) = data.split_at_mut(words * 4);
+    let mut cks = Zeroizing::new([0_u32; FUSED_BLOCK]);
+    let mut iks = Zeroizing::new([0_u32; FUSED_BLOCK]);
+    for block in body.chunks_mut(FUSED_BLOCK * 4) {
+        let n = block.len() / 4;
+        {
+            let mut lanes = [&mut cipher, &mut mac.zuc];
+            for k in 0..n {
+                [cks[k], iks[k]] = ZucKeystream::generate_lanes(&mut lanes);
+            }
+        }
+
+        if order == MacOrder::MacThenEncrypt {
+            mac.update_words(block, &iks[..n]);
+        }
+        for (chunk, &k) in block.as_chunks_mut_::<4>().0.iter_mut().zip(cks.iter()) {
+            xor_word(chunk, k);
+        }
+        if order == MacOrder::EncryptThenMac {
+            mac.update_words(block, &iks[..n]);
+        }
+    }
+
+    let rest_bitlen = bitlen - words * 32;
+    match order {
+        MacOrder::MacThenEncrypt => {
+            let tag = mac.finalize(rest, rest_bitlen, MacProfile::Zuc128);
+            xor_inplace(&mut cipher, rest, rest_bitlen);
+            tag
+        }
+        MacOrder::EncryptThenMac => {
+            xor_inplace(&mut cipher, rest, rest_bitlen);
+            mac.finalize(rest, rest_bitlen, MacProfile::Zuc128)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::eea3::eea3_xor_inplace;
+
+    use numeric_cast::TruncatingCast;
+
+    #[test]
+    fn separate() {
+        let (ck, ik) = ([0x4b; 16], [0xd2; 16]);
+        let (count, bearer, direction) = (0x3981_2f02, 0x0c, 1);
+        let msg: [u8; 150] = core::array::from_fn(|i| (i * 89 + 7).truncating_cast::<u8>());
+
+        for bitlen in [0, 1, 31, 32, 33, 64, 511, 512, 513, 1000, 1199, 1200] {
+            let mut encrypted = msg;
+            eea3_xor_inplace(count, bearer, direction, &ck, &mut encrypted, bitlen);
+
+            let mut data = msg;
+            let order = MacOrder::MacThenEncrypt;
+            let tag =
+                eea3_eia3_inplace(count, bearer, direction, &ck, &ik, &mut data, bitlen, order);
+            assert_eq!(data, encrypted);
+            assert_eq!(
+                tag,
+                Eia3Mac::compute(count, bearer, direction, &ik, &msg, bitlen)
+            );
+
+            let mut data = msg;
+            let order = MacOrder::EncryptThenMac;
+            let tag =
+                eea3_eia3_inplace(count, bearer, direction, &ck, &ik, &mut data, bitlen, order);
+            assert_eq!(data, encrypted);
+            assert_eq!(
+                tag,
+                Eia3Mac::compute(count, bearer, direction, &ik, &encrypted, bitlen)
+            );
+
+            // decrypting an encrypt-then-MAC bitstream verifies the MAC of the input
+            let order = MacOrder::MacThenEncrypt;
+            let check =
+                eea3_eia3_inplace(count, bearer, direction, &ck, &ik, &mut data, bitlen, order);
+            assert_eq!(check, tag);
+            assert_eq!(data[..bitlen / 8], msg[..bitlen / 8]);
+        }
+    }
+}
@@ -1,14 +1,24 @@
-use crate::internal::keystream::Keystream;
+use crate::internal::keystream::{Keystream, Sealed, ZucKeystream};
+use crate::internal::seek::Position;
+use crate::internal::zuc::Zuc;
+use crate::state::{algorithm, KeystreamState, StateError};
 use crate::zuc128::Zuc128Keystream;
 
 /// 128-EEA3 keystream generator
 /// ([EEA3-EIA3-specification](https://www.gsma.com/solutions-and-impact/technologies/security/wp-content/uploads/2019/05/EEA3_EIA3_specification_v1_8.pdf))
+#[derive(Clone)]
 pub struct Eea3Keystream(Zuc128Keystream);
 
 impl Eea3Keystream {
     /// Creates a 128-EEA3 keystream generator
     #[must_use]
     pub fn new(count: u32, bearer: u8, direction: u8, ck: &[u8; 16]) -> Self {
+        let iv = Self::iv(count, bearer, direction);
+        Self(Zuc128Keystream::new(ck, &iv))
+    }
+
+    /// Constructs the ZUC128 iv
+    pub(crate) fn iv(count: u32, bearer: u8, direction: u8) -> [u8; 16] {
         let bearer = bearer & 0x1f;
         let direction = direction & 0x01;
         let count = count.to_be_bytes();
@@ -26,23 +36,91 @@ impl Eea3Keystream {
         iv[11] = iv[3];
         iv[12] = iv[4];
 
-        Self(Zuc128Keystream::new(ck, &iv))
+        iv
     }
 
     ///  Generates the next 32-bit word in 128-EEA3 keystream
     pub fn generate(&mut self) -> u32 {
         self.0.generate()
     }
+
+    /// Returns the number of words generated since initialization
+    #[must_use]
+    pub fn position(&self) -> Position {
+        self.0.position()
+    }
+
+    /// Steps back by one word and returns the word generated last
+    ///
+    /// See [`Zuc128Keystream::step_back`].
+    ///
+    /// # Panics
+    /// + Panics if no word has been generated.
+    pub fn step_back(&mut self) -> u32 {
+        self.0.step_back()
+    }
+
+    /// Forks the keystream generator at its current position
+    #[must_use]
+    pub fn fork(&self) -> Self {
+        self.clone()
+    }
+
+    /// Exports the state of the keystream generator
+    ///
+    /// See [`KeystreamState`] for the format and the security notes.
+    #[must_use]
+    pub fn export_state(&self) -> KeystreamState {
+        KeystreamState::export(algorithm::EEA3, self)
+    }
+
+    /// Creates a 128-EEA3 keystream generator from an exported state
+    ///
+    /// # Errors
+    /// Returns an error if the state is exported from another algorithm.
+    pub fn import_state(state: &KeystreamState) -> Result<Self, StateError> {
+        state.import(algorithm::EEA3)
+    }
 }
 
-impl Keystream for Eea3Keystream {
-    type Word = u32;
+impl Sealed for Eea3Keystream {}
 
-    fn next_key(&mut self) -> Self::Word {
+impl Keystream for Eea3Keystream {
+    fn next_key(&mut self) -> u32 {
         self.generate()
     }
 }
 
+impl ZucKeystream for Eea3Keystream {
+    fn from_core(core: Zuc) -> Self {
+        Self(Zuc128Keystream::from_core(core))
+    }
+
+    fn resume(core: Zuc, pos: u64) -> Self {
+        Self(Zuc128Keystream::resume(core, pos))
+    }
+
+    fn core(&self) -> &Zuc {
+        self.0.core()
+    }
+
+    fn core_mut(&mut self) -> &mut Zuc {
+        self.0.core_mut()
+    }
+
+    fn parts_mut(&mut self) -> (&mut Zuc, &mut u64) {
+        self.0.parts_mut()
+    }
+
+    fn position(&self) -> u64 {
+        self.0.position().words()
+    }
+
+    fn step_back(&mut self) -> u32 {
+        self.0.step_back()
+    }
+}
+
 impl Iterator for Eea3Keystream {
     type Item = u32;
 
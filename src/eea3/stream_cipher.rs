@@ -1,11 +1,36 @@
 use super::Eea3Keystream;
 
-use crate::internal::stream_cipher::xor_inplace;
+#[cfg(feature = "rayon")]
+use crate::internal::parallel::xor_batch_parallel;
+use crate::internal::seek::{impl_seekable, Checkpoints};
+use crate::internal::stream_cipher::{xor_batch, xor_inplace, XorJob};
+use crate::internal::zuc::Zuc;
+use crate::zuc128::Zuc128Keystream;
 
 /// 128-EEA3 stream cipher
 /// ([EEA3-EIA3-specification](https://www.gsma.com/solutions-and-impact/technologies/security/wp-content/uploads/2019/05/EEA3_EIA3_specification_v1_8.pdf))
 pub type Eea3StreamCipher = cipher::StreamCipherCoreWrapper<Eea3Keystream>;
 
+/// Seekable 128-EEA3 stream cipher
+/// ([EEA3-EIA3-specification](https://www.gsma.com/solutions-and-impact/technologies/security/wp-content/uploads/2019/05/EEA3_EIA3_specification_v1_8.pdf))
+///
+/// It implements [`StreamCipherSeek`](cipher::StreamCipherSeek) with [`Eea3SeekableKeystream`].
+/// Create it with `Eea3SeekableStreamCipher::from_core(Eea3Keystream::new(..).into())`.
+pub type Eea3SeekableStreamCipher = cipher::StreamCipherCoreWrapper<Eea3SeekableKeystream>;
+
+/// Seekable 128-EEA3 keystream generator, the core of [`Eea3SeekableStreamCipher`]
+///
+/// See [`Zuc128SeekableKeystream`](crate::zuc128::Zuc128SeekableKeystream) for how seeking works.
+#[derive(Clone)]
+pub struct Eea3SeekableKeystream {
+    /// keystream generator
+    zuc: Eea3Keystream,
+    /// states to seek from
+    checkpoints: Checkpoints,
+}
+
+impl_seekable!(Eea3SeekableKeystream, Eea3Keystream);
+
 /// 128-EEA3: 3GPP confidentiality algorithm
 /// ([EEA3-EIA3-specification](https://www.gsma.com/solutions-and-impact/technologies/security/wp-content/uploads/2019/05/EEA3_EIA3_specification_v1_8.pdf))
 ///
@@ -30,6 +55,74 @@ pub fn eea3_xor_inplace(
     xor_inplace(&mut eea3, data, bitlen);
 }
 
+/// 128-EEA3 over multiple bitstreams
+///
+/// The keystream generation of independent jobs is interleaved.
+pub fn eea3_xor_inplace_batch(jobs: &mut [Eea3XorJob<'_>]) {
+    xor_batch(jobs);
+}
+
+/// 128-EEA3 over multiple bitstreams on multiple threads
+///
+/// Groups of jobs run on the rayon thread pool, each as in [`eea3_xor_inplace_batch`].
+/// The output of each job does not depend on the grouping.
+#[cfg(feature = "rayon")]
+pub fn eea3_xor_inplace_parallel(jobs: &mut [Eea3XorJob<'_>]) {
+    xor_batch_parallel(jobs);
+}
+
+/// 128-EEA3 job for [`eea3_xor_inplace_batch`]
+#[derive(Debug)]
+pub struct Eea3XorJob<'a> {
+    /// counter
+    pub count: u32,
+    /// carrier layer identification
+    pub bearer: u8,
+    /// transmission direction identification
+    pub direction: u8,
+    /// confidentiality key
+    pub ck: &'a [u8; 16],
+    /// the bitstream
+    pub data: &'a mut [u8],
+    /// bit length of the bitstream
+    pub bitlen: usize,
+}
+
+impl<'a> Eea3XorJob<'a> {
+    /// Create a new 128-EEA3 job
+    #[must_use]
+    pub fn new(
+        count: u32,
+        bearer: u8,
+        direction: u8,
+        ck: &'a [u8; 16],
+        data: &'a mut [u8],
+        bitlen: usize,
+    ) -> Self {
+        Self {
+            count,
+            bearer,
+            direction,
+            ck,
+            data,
+            bitlen,
+        }
+    }
+}
+
+impl XorJob for Eea3XorJob<'_> {
+    type Keystream = Eea3Keystream;
+
+    fn load(&self) -> Zuc {
+        let iv = Eea3Keystream::iv(self.count, self.bearer, self.direction);
+        Zuc128Keystream::load(self.ck, &iv)
+    }
+
+    fn data(&mut self) -> (&mut [u8], usize) {
+        (self.data, self.bitlen)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -223,6 +316,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn batch() {
+        let mut bufs: [_; 5] = core::array::from_fn(|i| ALL_EXAMPLES[i].ibs.to_vec());
+        let mut lanes = bufs.iter_mut().zip(ALL_EXAMPLES);
+        let mut jobs: [_; 5] = core::array::from_fn(|_| {
+            let (data, x) = lanes.next().unwrap();
+            let bitlen = x.length as usize;
+            Eea3XorJob::new(x.count, x.bearer, x.direction, &x.ck, data, bitlen)
+        });
+        eea3_xor_inplace_batch(&mut jobs);
+
+        for (data, x) in bufs.iter().zip(ALL_EXAMPLES) {
+            assert_eq!(data, x.obs);
+        }
+    }
+
+    #[test]
+    fn seek() {
+        use cipher::{StreamCipher, StreamCipherSeek};
+
+        let x = &EXAMPLE5;
+        let end = x.length as usize / 8;
+
+        let zuc = Eea3Keystream::new(x.count, x.bearer, x.direction, &x.ck);
+        let mut zuc = Eea3SeekableKeystream::new(zuc);
+        zuc.set_checkpoint_spacing(16);
+        let mut cipher = Eea3SeekableStreamCipher::from_core(zuc);
+
+        for offset in [0, 301, 7, 64, 63, 500, 1, 128] {
+            let mut data = x.ibs[offset..end].to_vec();
+            cipher.seek(offset);
+            cipher.apply_keystream(&mut data);
+            assert_eq!(data, x.obs[offset..end]);
+            assert_eq!(cipher.current_pos::<usize>(), end);
+        }
+    }
+
     #[should_panic(expected = "assertion failed: bitlen <= data.len() * 8")]
     #[test]
     fn invalid_input() {
@@ -1,10 +1,15 @@
 //! 128-EIA3: 3GPP Integrity algorithm
 
-use crate::internal::mac::MacWord;
-use crate::zuc128::Zuc128Mac;
+use crate::internal::mac::{compute_batch, MacCore, MacJob, MacWord};
+#[cfg(feature = "rayon")]
+use crate::internal::parallel::compute_batch_parallel;
+use crate::internal::zuc::Zuc;
+use crate::state::{algorithm, MacState, StateError};
+use crate::zuc128::{Zuc128Keystream, Zuc128Mac};
 
 /// 128-EIA3: 3GPP Integrity algorithm
 /// ([EEA3-EIA3-specification](https://www.gsma.com/solutions-and-impact/technologies/security/wp-content/uploads/2019/05/EEA3_EIA3_specification_v1_8.pdf))
+#[derive(Clone)]
 pub struct Eia3Mac(Zuc128Mac);
 
 impl Eia3Mac {
@@ -34,9 +39,71 @@ impl Eia3Mac {
         Self::new(count, bearer, direction, ik).finish(msg, bitlen)
     }
 
+    /// Compute the MACs of multiple messages
+    ///
+    /// The keystream generation of independent jobs is interleaved.
+    /// The MAC of each job is stored in [`Eia3MacJob::tag`].
+    pub fn compute_batch(jobs: &mut [Eia3MacJob<'_>]) {
+        compute_batch(jobs);
+    }
+
+    /// Compute the MAC of a message on multiple threads
+    ///
+    /// The result is identical to [`compute`](Self::compute).
+    ///
+    /// # Panics
+    /// + Panics if `bitlen` is greater than the bit length of `msg`.
+    #[cfg(feature = "rayon")]
+    #[must_use]
+    pub fn compute_parallel(
+        count: u32,
+        bearer: u8,
+        direction: u8,
+        ik: &[u8; 16],
+        msg: &[u8],
+        bitlen: usize,
+    ) -> u32 {
+        let iv = Self::iv(count, bearer, direction);
+        Zuc128Mac::compute_parallel(ik, &iv, msg, bitlen)
+    }
+
+    /// Compute the MACs of multiple messages on multiple threads
+    ///
+    /// Groups of jobs run on the rayon thread pool, each as in [`compute_batch`](Self::compute_batch).
+    /// The MAC of each job is stored in [`Eia3MacJob::tag`] and does not depend on the grouping.
+    #[cfg(feature = "rayon")]
+    pub fn compute_batch_parallel(jobs: &mut [Eia3MacJob<'_>]) {
+        compute_batch_parallel(jobs);
+    }
+
+    /// Compute the MAC of a message with a pregenerated keystream
+    ///
+    /// The keystream starts at the first word after initialization
+    /// and has [`keystream_len(bitlen)`](Self::keystream_len) words.
+    ///
+    /// # Panics
+    /// + Panics if the keystream length is not [`keystream_len(bitlen)`](Self::keystream_len).
+    /// + Panics if `bitlen` is greater than the bit length of `msg`.
+    #[must_use]
+    pub fn compute_with_keystream(keystream: &[u32], msg: &[u8], bitlen: usize) -> u32 {
+        Zuc128Mac::compute_with_keystream(keystream, msg, bitlen)
+    }
+
+    /// Number of keystream words used by the MAC of a `bitlen`-bit message
+    #[must_use]
+    pub fn keystream_len(bitlen: usize) -> usize {
+        Zuc128Mac::keystream_len(bitlen)
+    }
+
     /// Create a 128-EIA3 MAC generator
     #[must_use]
     pub fn new(count: u32, bearer: u8, direction: u8, ik: &[u8; 16]) -> Self {
+        let iv = Self::iv(count, bearer, direction);
+        Self(Zuc128Mac::new(ik, &iv))
+    }
+
+    /// Constructs the ZUC128 iv
+    pub(crate) fn iv(count: u32, bearer: u8, direction: u8) -> [u8; 16] {
         let mut iv: [u8; 16] = [0; 16];
         let count: [u8; 4] = count.to_be_bytes();
         let bearer = bearer & 0x1f;
@@ -53,7 +120,31 @@ impl Eia3Mac {
         iv[12] = iv[4];
         iv[14] = iv[6] ^ (direction << 7);
 
-        Self(Zuc128Mac::new(ik, &iv))
+        iv
+    }
+
+    /// Forks the MAC generator after the message absorbed so far
+    #[must_use]
+    pub fn fork(&self) -> Self {
+        self.clone()
+    }
+
+    /// Exports the state of the MAC generator
+    ///
+    /// See [`MacState`] for the format and the security notes.
+    #[must_use]
+    pub fn export_state(&self) -> MacState {
+        MacState::export(algorithm::EIA3, &self.0 .0)
+    }
+
+    /// Creates a 128-EIA3 MAC generator from an exported state
+    ///
+    /// # Errors
+    /// Returns an error if the state is exported from another algorithm or MAC size.
+    pub fn import_state(state: &MacState) -> Result<Self, StateError> {
+        state
+            .import(algorithm::EIA3)
+            .map(|core| Self(Zuc128Mac(core)))
     }
 
     /// Update the MAC generator with the bytes of a message
@@ -68,6 +159,70 @@ impl Eia3Mac {
     }
 }
 
+/// 128-EIA3 MAC job for [`Eia3Mac::compute_batch`]
+#[derive(Debug, Clone)]
+pub struct Eia3MacJob<'a> {
+    /// counter
+    pub count: u32,
+    /// carrier layer identification
+    pub bearer: u8,
+    /// transmission direction identification
+    pub direction: u8,
+    /// integrity key
+    pub ik: &'a [u8; 16],
+    /// the input message
+    pub msg: &'a [u8],
+    /// bit length of the input message
+    pub bitlen: usize,
+    /// the output MAC
+    pub tag: Option<u32>,
+}
+
+impl<'a> Eia3MacJob<'a> {
+    /// Create a new 128-EIA3 MAC job
+    #[must_use]
+    pub fn new(
+        count: u32,
+        bearer: u8,
+        direction: u8,
+        ik: &'a [u8; 16],
+        msg: &'a [u8],
+        bitlen: usize,
+    ) -> Self {
+        Self {
+            count,
+            bearer,
+            direction,
+            ik,
+            msg,
+            bitlen,
+            tag: None,
+        }
+    }
+}
+
+impl<'a> MacJob<'a> for Eia3MacJob<'a> {
+    type Keystream = Zuc128Keystream;
+    type Word = u32;
+
+    fn load(&self) -> Zuc {
+        let iv = Eia3Mac::iv(self.count, self.bearer, self.direction);
+        Zuc128Keystream::load(self.ik, &iv)
+    }
+
+    fn start(&self, zuc: Zuc128Keystream) -> MacCore<Zuc128Keystream, u32> {
+        Zuc128Mac::from_keystream(zuc).0
+    }
+
+    fn message(&self) -> (&'a [u8], usize) {
+        (self.msg, self.bitlen)
+    }
+
+    fn complete(&mut self, core: MacCore<Zuc128Keystream, u32>, tail: &[u8], bitlen: usize) {
+        self.tag = Some(Zuc128Mac(core).finish(tail, bitlen));
+    }
+}
+
 impl digest::Update for Eia3Mac {
     fn update(&mut self, data: &[u8]) {
         Eia3Mac::update(self, data);
@@ -297,4 +452,94 @@ mod tests {
 
         require_digest_mac::<Eia3Mac>();
     }
+
+    #[test]
+    fn batch() {
+        let mut jobs: [_; 5] = core::array::from_fn(|i| {
+            let x = ALL_EXAMPLES[i];
+            Eia3MacJob::new(
+                x.count,
+                x.bearer,
+                x.direction,
+                &x.ik,
+                x.m,
+                x.length as usize,
+            )
+        });
+        Eia3Mac::compute_batch(&mut jobs);
+
+        for (job, x) in jobs.iter().zip(ALL_EXAMPLES) {
+            assert_eq!(job.tag, Some(x.mac));
+        }
+
+        let x = &EXAMPLE5;
+        let mut jobs: [_; 9] = core::array::from_fn(|i| {
+            let bitlen = x.length as usize - i * 97;
+            Eia3MacJob::new(x.count, x.bearer, x.direction, &x.ik, x.m, bitlen)
+        });
+        Eia3Mac::compute_batch(&mut jobs);
+
+        for job in &jobs {
+            let expected = Eia3Mac::compute(x.count, x.bearer, x.direction, &x.ik, x.m, job.bitlen);
+            assert_eq!(job.tag, Some(expected));
+        }
+    }
+
+    #[should_panic(expected = "assertion failed: bitlen <= msg.len() * 8")]
+    #[test]
+    fn batch_invalid_input() {
+        let x = &EXAMPLE2;
+        let bitlen = x.length as usize * 2;
+        let mut jobs = [Eia3MacJob::new(
+            x.count,
+            x.bearer,
+            x.direction,
+            &x.ik,
+            x.m,
+            bitlen,
+        )];
+        Eia3Mac::compute_batch(&mut jobs);
+    }
+
+    #[test]
+    fn fork() {
+        for x in ALL_EXAMPLES {
+            let bitlen = x.length as usize;
+            let split = bitlen / 16;
+
+            let mut prefix = Eia3Mac::new(x.count, x.bearer, x.direction, &x.ik);
+            prefix.update(&x.m[..split]);
+
+            let forked = prefix.fork().finish(&x.m[split..], bitlen - split * 8);
+            assert_eq!(forked, x.mac);
+
+            // a different suffix does not affect the other forks
+            let other = prefix.fork().finish(&[0xff], 8);
+            let mut msg = [0; 1024];
+            msg[..split].copy_from_slice(&x.m[..split]);
+            msg[split] = 0xff;
+            let expected =
+                Eia3Mac::compute(x.count, x.bearer, x.direction, &x.ik, &msg, split * 8 + 8);
+            assert_eq!(other, expected);
+
+            assert_eq!(prefix.finish(&x.m[split..], bitlen - split * 8), x.mac);
+        }
+    }
+
+    #[test]
+    fn with_keystream() {
+        let mut keystream = [0; 256];
+        for x in ALL_EXAMPLES {
+            let bitlen = x.length as usize;
+            let n = Eia3Mac::keystream_len(bitlen);
+            let iv = Eia3Mac::iv(x.count, x.bearer, x.direction);
+            let mut zuc = Zuc128Keystream::new(&x.ik, &iv);
+            keystream[..n].fill_with(|| zuc.generate());
+
+            assert_eq!(
+                Eia3Mac::compute_with_keystream(&keystream[..n], x.m, bitlen),
+                x.mac
+            );
+        }
+    }
 }
@@ -0,0 +1,335 @@
+//! Bitsliced ZUC implementation
+//!
+//! Bit `i` of every lane is stored in the same machine word,
+//! so that one bitwise instruction processes all lanes at once.
+//! The S-boxes are evaluated from their algebraic normal forms,
+//! thus no table lookups depend on secret data.
+
+use super::zuc::{Zuc, S0, S1};
+
+use core::array;
+use core::fmt;
+use core::ops::{BitAnd, BitOr, BitXor, Not};
+
+/// Machine word holding one bit of each lane
+pub trait BitLanes
+where
+    Self: Sized + Copy + Eq + fmt::Debug,
+    Self: BitAnd<Output = Self> + BitOr<Output = Self> + BitXor<Output = Self> + Not<Output = Self>,
+{
+    /// Number of lanes
+    const LANES: usize;
+
+    /// All lanes are zero
+    const ZERO: Self;
+
+    /// All lanes are one
+    const ONES: Self;
+
+    /// get the bit of a lane
+    fn get(self, lane: usize) -> u32;
+
+    /// set the bit of a lane
+    fn set(&mut self, lane: usize, bit: u32);
+}
+
+macro_rules! impl_bit_lanes {
+    ($ty:ty) => {
+        impl BitLanes for $ty {
+            const LANES: usize = <$ty>::BITS as usize;
+
+            const ZERO: Self = 0;
+
+            const ONES: Self = <$ty>::MAX;
+
+            #[inline(always)]
+            fn get(self, lane: usize) -> u32 {
+                ((self >> lane) & 1) as u32
+            }
+
+            #[inline(always)]
+            fn set(&mut self, lane: usize, bit: u32) {
+                *self = (*self & !(1 << lane)) | (<$ty>::from(bit & 1) << lane);
+            }
+        }
+    };
+}
+
+impl_bit_lanes!(u32);
+impl_bit_lanes!(u64);
+
+/// algebraic normal form of an 8-bit S-box
+///
+/// `anf[j]` is the bitmap of the monomials of output bit `j`,
+/// where monomial `u` is the product of the input bits set in `u`.
+const fn anf(sbox: &[u8; 256]) -> [[u64; 4]; 8] {
+    let mut anf = [[0; 4]; 8];
+    let mut j = 0;
+    while j < 8 {
+        let mut truth = [0_u8; 256];
+        let mut x = 0;
+        while x < 256 {
+            truth[x] = (sbox[x] >> j) & 1;
+            x += 1;
+        }
+
+        // Möbius transform
+        let mut i = 0;
+        while i < 8 {
+            let mut x = 0;
+            while x < 256 {
+                if x & (1 << i) != 0 {
+                    truth[x] ^= truth[x ^ (1 << i)];
+                }
+                x += 1;
+            }
+            i += 1;
+        }
+
+        let mut u = 0;
+        while u < 256 {
+            anf[j][u / 64] |= (truth[u] as u64) << (u % 64);
+            u += 1;
+        }
+        j += 1;
+    }
+    anf
+}
+
+/// ANF of S0 box
+static S0_ANF: [[u64; 4]; 8] = anf(&S0);
+
+/// ANF of S1 box
+static S1_ANF: [[u64; 4]; 8] = anf(&S1);
+
+/// evaluate an 8-bit S-box on bitsliced bytes (`x[0]` is the lowest bit)
+#[inline(always)]
+fn sbox8<L: BitLanes>(anf: &[[u64; 4]; 8], x: [L; 8]) -> [L; 8] {
+    let mut monomials = [L::ONES; 256];
+    for u in 1..256_usize {
+        let high = usize::BITS - 1 - u.leading_zeros();
+        monomials[u] = monomials[u ^ (1 << high)] & x[high as usize];
+    }
+
+    array::from_fn(|j| {
+        let mut y = L::ZERO;
+        for (i, &coef) in anf[j].iter().enumerate() {
+            let mut coef = coef;
+            while coef != 0 {
+                let u = i * 64 + coef.trailing_zeros() as usize;
+                y = y ^ monomials[u];
+                coef &= coef - 1;
+            }
+        }
+        y
+    })
+}
+
+/// 32-bit bitsliced word (`x[0]` is the lowest bit)
+type Word<L> = [L; 32];
+
+/// 31-bit bitsliced word (`x[0]` is the lowest bit)
+type Word31<L> = [L; 31];
+
+/// (a + b) mod (2^32)
+#[inline(always)]
+fn add<L: BitLanes>(a: &Word<L>, b: &Word<L>) -> Word<L> {
+    let mut carry = L::ZERO;
+    array::from_fn(|i| {
+        let t = a[i] ^ b[i];
+        let s = t ^ carry;
+        carry = (a[i] & b[i]) | (carry & t);
+        s
+    })
+}
+
+/// (a + b) mod (2^31 - 1)
+///
+/// NOTE: the result is never zero if any operand is not zero,
+///       which is the same representation as the scalar implementation.
+#[inline(always)]
+fn add31<L: BitLanes>(a: &Word31<L>, b: &Word31<L>) -> Word31<L> {
+    let mut carry = L::ZERO;
+    let mut sum: Word31<L> = array::from_fn(|i| {
+        let t = a[i] ^ b[i];
+        let s = t ^ carry;
+        carry = (a[i] & b[i]) | (carry & t);
+        s
+    });
+
+    // end-around carry, which never overflows again
+    for x in &mut sum {
+        let s = *x ^ carry;
+        carry = *x & carry;
+        *x = s;
+    }
+
+    sum
+}
+
+/// (2^n * x) mod (2^31 - 1)
+#[inline(always)]
+fn rol31<L: BitLanes>(x: &Word31<L>, n: usize) -> Word31<L> {
+    array::from_fn(|i| x[(i + 31 - n) % 31])
+}
+
+/// XOR of rotations
+#[inline(always)]
+fn xor_rol<L: BitLanes>(x: &Word<L>, ns: [usize; 4]) -> Word<L> {
+    array::from_fn(|i| {
+        let mut y = x[i];
+        for n in ns {
+            y = y ^ x[(i + 32 - n) % 32];
+        }
+        y
+    })
+}
+
+/// L1 linear transform
+#[inline(always)]
+fn l1<L: BitLanes>(x: &Word<L>) -> Word<L> {
+    xor_rol(x, [2, 10, 18, 24])
+}
+
+/// L2 linear transform
+#[inline(always)]
+fn l2<L: BitLanes>(x: &Word<L>) -> Word<L> {
+    xor_rol(x, [8, 14, 22, 30])
+}
+
+/// S box transform
+#[inline(always)]
+fn sbox<L: BitLanes>(x: &Word<L>) -> Word<L> {
+    let mut y = [L::ZERO; 32];
+    for (k, anf) in [(3, &S0_ANF), (2, &S1_ANF), (1, &S0_ANF), (0, &S1_ANF)] {
+        let byte = sbox8(anf, array::from_fn(|i| x[k * 8 + i]));
+        y[k * 8..k * 8 + 8].copy_from_slice(&byte);
+    }
+    y
+}
+
+/// `(high << 16) | (low >> (n - 16))` truncated to 32 bits, where `low` has `n` bits
+#[inline(always)]
+fn concat<L: BitLanes>(high: &[L], low: &[L], n: usize) -> Word<L> {
+    array::from_fn(|i| {
+        if i < 16 {
+            low[n - 16 + i]
+        } else {
+            high[i - 16]
+        }
+    })
+}
+
+/// Bitsliced ZUC keystream generator
+#[derive(Clone)]
+pub struct BitslicedZuc<L: BitLanes> {
+    /// LFSR registers (31-bit words x16)
+    s: [Word31<L>; 16],
+
+    /// R1 state unit (32 bits)
+    r1: Word<L>,
+
+    /// R2 state unit (32 bits)
+    r2: Word<L>,
+}
+
+impl<L: BitLanes> BitslicedZuc<L> {
+    /// Transposes the loaded states of scalar generators, one per lane
+    ///
+    /// Lanes without a state are zero.
+    pub fn load<'z>(lanes: impl IntoIterator<Item = &'z Zuc>) -> Self {
+        let mut this = Self {
+            s: [[L::ZERO; 31]; 16],
+            r1: [L::ZERO; 32],
+            r2: [L::ZERO; 32],
+        };
+        for (lane, zuc) in lanes.into_iter().enumerate() {
+            assert!(lane < L::LANES);
+            for j in 0..16 {
+                for i in 0..31 {
+                    this.s[j][i].set(lane, zuc.s[j] >> i);
+                }
+            }
+            for i in 0..32 {
+                this.r1[i].set(lane, zuc.r1 >> i);
+                this.r2[i].set(lane, zuc.r2 >> i);
+            }
+        }
+        this
+    }
+
+    /// Initializes all lanes
+    pub fn init(&mut self) {
+        for _ in 0..32 {
+            let x = self.bit_reconstruction();
+            let w = self.f(&x);
+            let u: Word31<L> = array::from_fn(|i| w[i + 1]);
+            self.lfsr(Some(&u));
+        }
+
+        {
+            let x = self.bit_reconstruction();
+            self.f(&x);
+        }
+    }
+
+    /// `BitReconstruction` function
+    fn bit_reconstruction(&self) -> [Word<L>; 4] {
+        let s = &self.s;
+        [
+            concat(&s[15][15..], &s[14], 16),
+            concat(&s[11], &s[9], 31),
+            concat(&s[7], &s[5], 31),
+            concat(&s[2], &s[0], 31),
+        ]
+    }
+
+    /// F non-linear function
+    fn f(&mut self, x: &[Word<L>; 4]) -> Word<L> {
+        let x0_r1: Word<L> = array::from_fn(|i| x[0][i] ^ self.r1[i]);
+        let w = add(&x0_r1, &self.r2);
+        let w1 = add(&self.r1, &x[1]);
+        let w2: Word<L> = array::from_fn(|i| self.r2[i] ^ x[2][i]);
+        self.r1 = sbox(&l1(&concat(&w1, &w2, 32)));
+        self.r2 = sbox(&l2(&concat(&w2, &w1, 32)));
+        w
+    }
+
+    /// `LFSRWithInitialisationMode` or `LFSRWithWorkMode` function
+    fn lfsr(&mut self, u: Option<&Word31<L>>) {
+        let s = &self.s;
+        let mut v = add31(&s[0], &rol31(&s[0], 8));
+        v = add31(&v, &rol31(&s[4], 20));
+        v = add31(&v, &rol31(&s[10], 21));
+        v = add31(&v, &rol31(&s[13], 17));
+        v = add31(&v, &rol31(&s[15], 15));
+        if let Some(u) = u {
+            v = add31(&v, u);
+        }
+
+        self.s.copy_within(1.., 0);
+        self.s[15] = v;
+    }
+
+    /// Generates the next 32-bit word of every lane
+    pub fn generate(&mut self, out: &mut [u32]) {
+        assert!(out.len() <= L::LANES);
+
+        self.lfsr(None);
+        let x = self.bit_reconstruction();
+        let w = self.f(&x);
+
+        for (lane, z) in out.iter_mut().enumerate() {
+            *z = 0;
+            for i in 0..32 {
+                *z |= (w[i] ^ x[3][i]).get(lane) << i;
+            }
+        }
+    }
+}
+
+impl<L: BitLanes> fmt::Debug for BitslicedZuc<L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BitslicedZuc").finish_non_exhaustive()
+    }
+}
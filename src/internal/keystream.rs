@@ -1,5 +1,274 @@
-pub trait Keystream {
-    type Word;
+use super::zuc::Zuc;
 
-    fn next_key(&mut self) -> Self::Word;
+use core::array;
+
+use stdx::slice::SliceExt as _;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+pub(crate) use self::sealed::Sealed;
+
+/// ZUC keystream
+///
+/// It is implemented by [`Zuc128Keystream`](crate::zuc128::Zuc128Keystream),
+/// [`Zuc256Keystream`](crate::zuc256::Zuc256Keystream) and [`Eea3Keystream`](crate::eea3::Eea3Keystream),
+/// and forwarded through `&mut K` and `Box<K>`.
+///
+/// This trait is sealed and cannot be implemented outside of this crate.
+pub trait Keystream: Sealed {
+    /// Generates the next 32-bit word
+    fn next_key(&mut self) -> u32;
+
+    /// Generates the next `N` words
+    fn take_words<const N: usize>(&mut self) -> [u32; N] {
+        array::from_fn(|_| self.next_key())
+    }
+
+    /// Fills `buf` with keystream bytes, most significant byte of each word first
+    ///
+    /// A partial word at the end consumes a whole word.
+    fn fill_bytes(&mut self, buf: &mut [u8]) {
+        let (chunks, tail) = buf.as_chunks_mut_::<4>();
+        for chunk in chunks {
+            *chunk = self.next_key().to_be_bytes();
+        }
+        if !tail.is_empty() {
+            let k = self.next_key().to_be_bytes();
+            tail.copy_from_slice(&k[..tail.len()]);
+        }
+    }
+
+    /// XORs the first `bitlen` bits of `data` with the keystream
+    ///
+    /// The unused bits of a partial last byte and the bytes after it are set to zero.
+    /// It consumes `bitlen.div_ceil(32)` words, as [`fill_bytes`](Self::fill_bytes) does for the bytes holding `bitlen` bits.
+    ///
+    /// # Panics
+    /// + Panics if `bitlen` is greater than the bit length of `data`.
+    fn xor_bits(&mut self, data: &mut [u8], bitlen: usize) {
+        assert!(bitlen <= data.len() * 8);
+
+        let (data, rest) = data.split_at_mut(bitlen.div_ceil(8));
+        let (chunks, tail) = data.as_chunks_mut_::<4>();
+        for chunk in chunks {
+            xor_word(chunk, self.next_key());
+        }
+        if !tail.is_empty() {
+            let k = self.next_key().to_be_bytes();
+            for (d, k) in tail.iter_mut().zip(k) {
+                *d ^= k;
+            }
+        }
+
+        if bitlen % 8 != 0 {
+            data[bitlen / 8] &= 0xFF << (8 - bitlen % 8);
+        }
+        rest.fill(0);
+    }
+}
+
+impl<K: Keystream + ?Sized> Sealed for &mut K {}
+
+impl<K: Keystream + ?Sized> Keystream for &mut K {
+    #[inline(always)]
+    fn next_key(&mut self) -> u32 {
+        (**self).next_key()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K: Keystream + ?Sized> Sealed for alloc::boxed::Box<K> {}
+
+#[cfg(feature = "alloc")]
+impl<K: Keystream + ?Sized> Keystream for alloc::boxed::Box<K> {
+    #[inline(always)]
+    fn next_key(&mut self) -> u32 {
+        (**self).next_key()
+    }
+}
+
+/// XOR a keystream word into 4 bytes
+#[inline(always)]
+pub fn xor_word(chunk: &mut [u8; 4], k: u32) {
+    let k = k.to_be_bytes();
+    for i in 0..4 {
+        chunk[i] ^= k[i];
+    }
+}
+
+/// Keystream generated by a ZUC core
+pub trait ZucKeystream: Keystream {
+    /// wrap a ZUC core
+    fn from_core(core: Zuc) -> Self;
+
+    /// wrap a ZUC core at the position `pos`
+    fn resume(core: Zuc, pos: u64) -> Self;
+
+    /// get the ZUC core
+    fn core(&self) -> &Zuc;
+
+    /// get the ZUC core
+    fn core_mut(&mut self) -> &mut Zuc;
+
+    /// get the ZUC core and the number of words generated by it
+    fn parts_mut(&mut self) -> (&mut Zuc, &mut u64);
+
+    /// get the number of words generated by [`Keystream::next_key`]
+    fn position(&self) -> u64;
+
+    /// step back by one word and return the word generated last
+    fn step_back(&mut self) -> u32;
+
+    /// generate the next words of independent keystreams in lockstep
+    ///
+    /// The position of every lane is advanced by one word.
+    #[inline(always)]
+    fn generate_lanes<const N: usize>(lanes: &mut [&mut Self; N]) -> [u32; N]
+    where
+        Self: Sized,
+    {
+        let mut cores = lanes.each_mut().map(|zuc| {
+            let (core, pos) = zuc.parts_mut();
+            *pos += 1;
+            core
+        });
+        Zuc::generate_lanes(&mut cores)
+    }
+}
+
+/// Independent ZUC keystreams advanced in lockstep
+///
+/// It is the portable backend of the batch algorithms.
+/// The lanes are updated stage by stage,
+/// so that the CPU can overlap their dependency chains without any SIMD instruction.
+#[derive(Debug, Clone)]
+pub struct Interleaved<K, const N: usize> {
+    lanes: [K; N],
+}
+
+impl<K: ZucKeystream, const N: usize> Interleaved<K, N> {
+    /// initialize loaded ZUC cores in lockstep
+    pub fn init(mut cores: [Zuc; N]) -> Self {
+        Zuc::init_lanes(&mut cores.each_mut());
+        Self {
+            lanes: cores.map(K::from_core),
+        }
+    }
+
+    /// interleave existing keystreams
+    pub fn from_lanes(lanes: [K; N]) -> Self {
+        Self { lanes }
+    }
+
+    /// split into the keystreams, each keeping its position
+    pub fn into_lanes(self) -> [K; N] {
+        self.lanes
+    }
+
+    /// generate the next word of every lane
+    #[inline(always)]
+    pub fn generate(&mut self) -> [u32; N] {
+        K::generate_lanes(&mut self.lanes.each_mut())
+    }
+}
+
+/// Keystream from pregenerated words
+pub struct SliceKeystream<'a>(core::slice::Iter<'a, u32>);
+
+impl<'a> SliceKeystream<'a> {
+    pub fn new(words: &'a [u32]) -> Self {
+        Self(words.iter())
+    }
+}
+
+impl Sealed for SliceKeystream<'_> {}
+
+impl Keystream for SliceKeystream<'_> {
+    #[inline(always)]
+    fn next_key(&mut self) -> u32 {
+        match self.0.next() {
+            Some(&k) => k,
+            None => panic!("keystream is exhausted"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::eea3::{eea3_xor_inplace, Eea3Keystream};
+    use crate::zuc256::Zuc256Keystream;
+
+    use core::cmp::Ordering;
+
+    fn words(mut zuc: impl Keystream) -> [u32; 8] {
+        zuc.take_words()
+    }
+
+    #[test]
+    fn provided() {
+        let new = || Zuc256Keystream::new(&[0x1f; 32], &[0x2e; 23]);
+        let expected: [u32; 8] = core::array::from_fn({
+            let mut zuc = new();
+            move |_| zuc.generate()
+        });
+
+        let mut zuc = new();
+        assert_eq!(words(&mut zuc), expected);
+        assert_eq!(zuc.position().words(), 8);
+
+        let mut buf = [0; 11];
+        new().fill_bytes(&mut buf);
+        for (chunk, k) in buf.chunks(4).zip(expected) {
+            assert_eq!(chunk, &k.to_be_bytes()[..chunk.len()]);
+        }
+    }
+
+    #[test]
+    fn xor_bits() {
+        let ck = [0x17; 16];
+        for bitlen in [0, 1, 31, 32, 33, 100, 128, 159] {
+            let mut data = [0xa5; 20];
+            let mut expected = data;
+            eea3_xor_inplace(0x1234, 3, 1, &ck, &mut expected, bitlen);
+
+            let mut ks = [0; 24];
+            Eea3Keystream::new(0x1234, 3, 1, &ck).fill_bytes(&mut ks);
+            for i in 0..20 {
+                let mask = match i.cmp(&(bitlen / 8)) {
+                    Ordering::Less => 0xff,
+                    Ordering::Equal => 0xff_u8 << ((8 - bitlen % 8) % 8),
+                    Ordering::Greater => 0,
+                };
+                assert_eq!(expected[i], (0xa5 ^ ks[i]) & mask);
+            }
+
+            // the byte after a whole number of bytes is cleared too
+            if bitlen % 8 == 0 {
+                expected[bitlen / 8] = 0;
+            }
+
+            let mut zuc = Eea3Keystream::new(0x1234, 3, 1, &ck);
+            zuc.xor_bits(&mut data, bitlen);
+            assert_eq!(data, expected);
+            assert_eq!(zuc.position().words(), bitlen.div_ceil(32) as u64);
+
+            // the stream continues right after the consumed words
+            let mut next = [0; 4];
+            zuc.fill_bytes(&mut next);
+            let i = bitlen.div_ceil(32) * 4;
+            assert_eq!(next, ks[i..i + 4]);
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn boxed() {
+        let zuc = Zuc256Keystream::new(&[0; 32], &[0; 23]);
+        let expected = words(zuc.clone());
+        assert_eq!(words(alloc::boxed::Box::new(zuc)), expected);
+    }
 }
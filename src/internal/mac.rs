@@ -1,6 +1,11 @@
-use super::keystream::Keystream;
+use super::keystream::{Interleaved, Keystream, SliceKeystream, ZucKeystream};
+#[cfg(not(any(target_pointer_width = "32", feature = "word32")))]
 use super::u256::U256;
+#[cfg(any(target_pointer_width = "32", feature = "word32"))]
+use super::window::KeyWindow;
+use super::zuc::Zuc;
 
+use core::array;
 use core::fmt;
 use core::mem::size_of;
 use core::ops::{BitXorAssign, ShlAssign};
@@ -9,11 +14,13 @@ use cipher::generic_array::typenum;
 use cipher::generic_array::ArrayLength;
 use cipher::generic_array::GenericArray;
 use numeric_cast::TruncatingCast;
+use stdx::default::default;
+use zeroize::{Zeroize, Zeroizing};
 
 /// Mac Word
 pub trait MacWord
 where
-    Self: Sized + Copy + Eq,
+    Self: Sized + Copy + Eq + Default + Send + Sync,
     Self: fmt::Debug + fmt::LowerHex + fmt::UpperHex,
     Self: BitXorAssign + ShlAssign<usize>,
 {
@@ -23,7 +30,7 @@ where
     type ByteSize: ArrayLength<u8>;
 
     /// generate word
-    fn gen_word(zuc: &mut impl Keystream<Word = u32>) -> Self;
+    fn gen_word(zuc: &mut impl Keystream) -> Self;
 
     /// convert key from big endian bytes
     fn from_be_slice(chunk: &[u8]) -> Self;
@@ -45,23 +52,91 @@ where
     type Word: MacWord<KeyPair = Self>;
 
     /// generate key pair
-    fn gen_key_pair(zuc: &mut impl Keystream<Word = u32>) -> Self;
+    fn gen_key_pair(zuc: &mut impl Keystream) -> Self;
 
     /// get high bits
     fn high(&self) -> Self::Word;
 
+    /// get low bits
+    fn low(&self) -> Self::Word;
+
     /// set low bits
     fn set_low(&mut self, low: Self::Word);
+
+    /// get the 32-bit words, most significant first
+    #[cfg(feature = "small")]
+    fn words_mut(&mut self) -> &mut [u32];
+}
+
+mod sealed {
+    pub trait Sealed: super::MacWord {}
+}
+
+/// Tag of ZUC MACs: `u32`, `u64` or `u128`
+///
+/// This trait is sealed and cannot be implemented outside of this crate.
+pub trait MacTag
+where
+    Self: sealed::Sealed + Copy + Eq + Default + Send + Sync + 'static,
+    Self: fmt::Debug + fmt::LowerHex + fmt::UpperHex,
+{
+    /// Byte size of the tag
+    type Size: ArrayLength<u8> + 'static;
+
+    /// Converts the tag to big endian bytes
+    fn to_bytes(self) -> GenericArray<u8, Self::Size>;
+
+    /// Converts big endian bytes to a tag
+    fn from_bytes(bytes: &GenericArray<u8, Self::Size>) -> Self;
+}
+
+macro_rules! impl_mac_tag {
+    ($($ty:ty),+) => {$(
+        impl sealed::Sealed for $ty {}
+
+        impl MacTag for $ty {
+            type Size = <$ty as MacWord>::ByteSize;
+
+            #[inline(always)]
+            fn to_bytes(self) -> GenericArray<u8, Self::Size> {
+                self.to_be_array()
+            }
+
+            #[inline(always)]
+            fn from_bytes(bytes: &GenericArray<u8, Self::Size>) -> Self {
+                Self::from_be_slice(bytes)
+            }
+        }
+    )+};
+}
+
+impl_mac_tag!(u32, u64, u128);
+
+/// Construction of ZUC MACs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MacProfile {
+    /// ZUC128 MAC and 128-EIA3
+    ///
+    /// The tag starts at zero and is masked at the end by the key word at the message end
+    /// and by the next keystream word.
+    Zuc128,
+    /// ZUC256 MAC
+    ///
+    /// The tag starts with the first keystream word and is masked at the end by the key word at the message end.
+    Zuc256,
 }
 
 // 32 bit word
 impl MacWord for u32 {
+    #[cfg(not(any(target_pointer_width = "32", feature = "word32")))]
     type KeyPair = u64;
+    #[cfg(any(target_pointer_width = "32", feature = "word32"))]
+    type KeyPair = KeyWindow<2>;
 
     type ByteSize = typenum::U4;
 
     #[inline(always)]
-    fn gen_word(zuc: &mut impl Keystream<Word = u32>) -> u32 {
+    fn gen_word(zuc: &mut impl Keystream) -> u32 {
         zuc.next_key()
     }
 
@@ -86,11 +161,12 @@ impl MacWord for u32 {
 }
 
 // key pair form 32 bit word
+#[cfg(not(any(target_pointer_width = "32", feature = "word32")))]
 impl MacKeyPair for u64 {
     type Word = u32;
 
     #[inline(always)]
-    fn gen_key_pair(zuc: &mut impl Keystream<Word = u32>) -> u64 {
+    fn gen_key_pair(zuc: &mut impl Keystream) -> u64 {
         u64::gen_word(zuc)
     }
 
@@ -99,6 +175,11 @@ impl MacKeyPair for u64 {
         (self >> 32) as u32
     }
 
+    #[inline(always)]
+    fn low(&self) -> u32 {
+        self.truncating_cast::<u32>()
+    }
+
     #[inline(always)]
     fn set_low(&mut self, low: Self::Word) {
         *self |= Self::from(low);
@@ -107,12 +188,15 @@ impl MacKeyPair for u64 {
 
 // 64 bit word
 impl MacWord for u64 {
+    #[cfg(not(any(target_pointer_width = "32", feature = "word32")))]
     type KeyPair = u128;
+    #[cfg(any(target_pointer_width = "32", feature = "word32"))]
+    type KeyPair = KeyWindow<4>;
 
     type ByteSize = typenum::U8;
 
     #[inline(always)]
-    fn gen_word(zuc: &mut impl Keystream<Word = u32>) -> u64 {
+    fn gen_word(zuc: &mut impl Keystream) -> u64 {
         (u64::from(zuc.next_key()) << 32) | u64::from(zuc.next_key())
     }
 
@@ -137,11 +221,12 @@ impl MacWord for u64 {
 }
 
 // key pair form 64 bit word
+#[cfg(not(any(target_pointer_width = "32", feature = "word32")))]
 impl MacKeyPair for u128 {
     type Word = u64;
 
     #[inline(always)]
-    fn gen_key_pair(zuc: &mut impl Keystream<Word = u32>) -> u128 {
+    fn gen_key_pair(zuc: &mut impl Keystream) -> u128 {
         u128::gen_word(zuc)
     }
 
@@ -150,6 +235,11 @@ impl MacKeyPair for u128 {
         (self >> 64) as u64
     }
 
+    #[inline(always)]
+    fn low(&self) -> u64 {
+        self.truncating_cast::<u64>()
+    }
+
     #[inline(always)]
     fn set_low(&mut self, low: Self::Word) {
         *self |= Self::from(low);
@@ -158,12 +248,15 @@ impl MacKeyPair for u128 {
 
 // 128 bit word
 impl MacWord for u128 {
+    #[cfg(not(any(target_pointer_width = "32", feature = "word32")))]
     type KeyPair = U256;
+    #[cfg(any(target_pointer_width = "32", feature = "word32"))]
+    type KeyPair = KeyWindow<8>;
 
     type ByteSize = typenum::U16;
 
     #[inline(always)]
-    fn gen_word(zuc: &mut impl Keystream<Word = u32>) -> u128 {
+    fn gen_word(zuc: &mut impl Keystream) -> u128 {
         let a = (
             u128::from(zuc.next_key()) << 96,
             u128::from(zuc.next_key()) << 64,
@@ -194,10 +287,11 @@ impl MacWord for u128 {
 }
 
 // key pair form 128 bit word
+#[cfg(not(any(target_pointer_width = "32", feature = "word32")))]
 impl MacKeyPair for U256 {
     type Word = u128;
 
-    fn gen_key_pair(zuc: &mut impl Keystream<Word = u32>) -> U256 {
+    fn gen_key_pair(zuc: &mut impl Keystream) -> U256 {
         let high = u128::gen_word(zuc);
         let low = u128::gen_word(zuc);
         U256::new(high, low)
@@ -207,6 +301,10 @@ impl MacKeyPair for U256 {
         self.high
     }
 
+    fn low(&self) -> u128 {
+        self.low
+    }
+
     fn set_low(&mut self, low: Self::Word) {
         self.low = low;
     }
@@ -217,6 +315,27 @@ fn copy(dst: &mut [u8], src: &[u8]) {
     dst[..src.len()].copy_from_slice(src);
 }
 
+/// Shared non-generic MAC core of the `small` feature
+///
+/// Feeds the first `nbits` bits of `msg` into `tag` with the key window `key`.
+/// Both are 32-bit words, most significant first, and `key` has twice as many words as `tag`.
+#[cfg(feature = "small")]
+#[inline(never)]
+fn feed_bits_small(msg: &[u8], nbits: usize, tag: &mut [u32], key: &mut [u32]) {
+    for i in 0..nbits {
+        if msg[i / 8] & (0x80 >> (i % 8)) != 0 {
+            for (t, k) in tag.iter_mut().zip(key.iter()) {
+                *t ^= *k;
+            }
+        }
+        for j in 0..key.len() {
+            let carry = key.get(j + 1).map_or(0, |k| k >> 31);
+            key[j] = (key[j] << 1) | carry;
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct MacCore<S, T>
 where
     S: Keystream,
@@ -228,13 +347,17 @@ where
 
     pub rem: GenericArray<u8, T::ByteSize>,
     pub cnt: u8,
+
+    /// keystream of the message words being fed, zeroized on drop
+    pub buf: Zeroizing<[u32; KEYSTREAM_BLOCK]>,
 }
 
 impl<S, T> MacCore<S, T>
 where
-    S: Keystream<Word = u32>,
+    S: Keystream,
     T: MacWord,
 {
+    #[cfg(not(feature = "small"))]
     #[inline(always)]
     fn xor_step(bits: &mut T, tag: &mut T, key: &mut T::KeyPair) {
         if bits.test_high_bit() {
@@ -244,14 +367,50 @@ where
         *key <<= 1;
     }
 
+    /// feed the high `nbits` bits of `bits`
+    #[cfg(not(feature = "small"))]
     #[inline(always)]
-    fn feed_word(mut bits: T, tag: &mut T, key: &mut T::KeyPair, zuc: &mut S) {
-        for _ in 0..size_of::<T>() * 8 {
+    fn feed_bits(mut bits: T, nbits: usize, tag: &mut T, key: &mut T::KeyPair) {
+        for _ in 0..nbits {
             Self::xor_step(&mut bits, tag, key);
         }
+    }
+
+    /// feed the high `nbits` bits of `bits` with the shared core
+    #[cfg(feature = "small")]
+    fn feed_bits(bits: T, nbits: usize, tag: &mut T, key: &mut T::KeyPair) {
+        let n = size_of::<T>() / 4;
+        let mut delta = [0; 4];
+        feed_bits_small(&bits.to_be_array(), nbits, &mut delta[..n], key.words_mut());
+
+        let mut buf = [0; 16];
+        for i in 0..n {
+            buf[i * 4..i * 4 + 4].copy_from_slice(&delta[i].to_be_bytes());
+        }
+        *tag ^= T::from_be_slice(&buf[..n * 4]);
+    }
+
+    #[inline(always)]
+    fn feed_word(bits: T, tag: &mut T, key: &mut T::KeyPair, zuc: &mut impl Keystream) {
+        Self::feed_bits(bits, size_of::<T>() * 8, tag, key);
         key.set_low(T::gen_word(zuc));
     }
 
+    /// feed whole words of message with pregenerated keystream, requires no buffered bytes
+    #[inline(always)]
+    pub fn update_words(&mut self, msg: &[u8], keystream: &[u32]) {
+        debug_assert_eq!(self.cnt, 0);
+        let mut zuc = SliceKeystream::new(keystream);
+        let mut key = self.key;
+        let mut tag = self.tag;
+        for chunk in msg.chunks_exact(size_of::<T>()) {
+            let bits = T::from_be_slice(chunk);
+            Self::feed_word(bits, &mut tag, &mut key, &mut zuc);
+        }
+        self.key = key;
+        self.tag = tag;
+    }
+
     pub fn update(&mut self, mut msg: &[u8]) {
         if msg.is_empty() {
             return;
@@ -278,22 +437,65 @@ where
             Self::feed_word(bits, &mut tag, &mut key, zuc);
         }
 
-        let mut chunks = msg.chunks_exact(size_of::<T>());
-        for chunk in &mut chunks {
-            let bits = T::from_be_slice(chunk);
-            Self::feed_word(bits, &mut tag, &mut key, zuc);
-        }
+        // the keystream is generated a block at a time,
+        // exactly for the whole words of this message, so that nothing is generated ahead
+        let (body, rest) = msg.split_at(msg.len() / size_of::<T>() * size_of::<T>());
+        for block in body.chunks(KEYSTREAM_BLOCK * 4) {
+            let buf = &mut self.buf[..block.len() / 4];
+            for k in &mut *buf {
+                *k = zuc.next_key();
+            }
 
-        {
-            let rest = chunks.remainder();
-            copy(rem, rest);
-            self.cnt = rest.len().truncating_cast::<u8>();
+            let mut keystream = SliceKeystream::new(buf);
+            for chunk in block.chunks_exact(size_of::<T>()) {
+                let bits = T::from_be_slice(chunk);
+                Self::feed_word(bits, &mut tag, &mut key, &mut keystream);
+            }
         }
 
+        copy(rem, rest);
+        self.cnt = rest.len().truncating_cast::<u8>();
+
         self.key = key;
         self.tag = tag;
     }
 
+    /// start a MAC with the initial tag and key pair of `profile`
+    pub fn new(mut zuc: S, profile: MacProfile) -> Self {
+        let tag = match profile {
+            MacProfile::Zuc128 => T::default(),
+            MacProfile::Zuc256 => T::gen_word(&mut zuc),
+        };
+        let key = T::KeyPair::gen_key_pair(&mut zuc);
+
+        Self {
+            zuc,
+            key,
+            tag,
+            rem: default(),
+            cnt: 0,
+            buf: default(),
+        }
+    }
+
+    /// finish the message and mask the tag as `profile`
+    pub fn finalize(&mut self, tail: &[u8], bitlen: usize, profile: MacProfile) -> T {
+        let final_bitlen = self.finish(tail, bitlen);
+
+        let mut tag = self.tag;
+        tag ^= self.key.high();
+
+        if profile == MacProfile::Zuc128 {
+            if final_bitlen == 0 {
+                tag ^= self.key.low();
+            } else {
+                tag ^= T::gen_word(&mut self.zuc);
+            }
+        }
+
+        tag
+    }
+
     #[must_use]
     pub fn finish(&mut self, mut tail: &[u8], mut bitlen: usize) -> usize {
         assert!(bitlen <= tail.len() * 8);
@@ -315,10 +517,8 @@ where
 
         let bitlen = cnt * 8 + bitlen;
         if bitlen != 0 {
-            let mut bits = T::from_be_slice(rem);
-            for _ in 0..bitlen {
-                Self::xor_step(&mut bits, &mut tag, &mut key);
-            }
+            let bits = T::from_be_slice(rem);
+            Self::feed_bits(bits, bitlen, &mut tag, &mut key);
 
             self.tag = tag;
             self.key = key;
@@ -327,3 +527,159 @@ where
         bitlen
     }
 }
+
+/// Number of keystream words used by the MAC of a `bitlen`-bit message
+pub fn keystream_len<T: MacWord>(bitlen: usize, profile: MacProfile) -> usize {
+    let n = size_of::<T>() / 4;
+    let word_bits = size_of::<T>() * 8;
+    match profile {
+        MacProfile::Zuc128 => (2 + bitlen.div_ceil(word_bits)) * n,
+        MacProfile::Zuc256 => (3 + bitlen / word_bits) * n,
+    }
+}
+
+/// Compute the MAC of a message with a pregenerated keystream
+///
+/// # Panics
+/// + Panics if the keystream length is not [`keystream_len`].
+/// + Panics if `bitlen` is greater than the bit length of `msg`.
+pub fn compute_with_keystream<T: MacWord>(
+    keystream: &[u32],
+    msg: &[u8],
+    bitlen: usize,
+    profile: MacProfile,
+) -> T {
+    assert_eq!(
+        keystream.len(),
+        keystream_len::<T>(bitlen, profile),
+        "keystream length does not match the message"
+    );
+    let mut core = MacCore::<_, T>::new(SliceKeystream::new(keystream), profile);
+    core.finalize(msg, bitlen, profile)
+}
+
+/// Number of keystream words generated at a time by [`MacCore::update`]
+const KEYSTREAM_BLOCK: usize = 16;
+
+/// Number of jobs interleaved by the batch algorithms
+pub const BATCH_LANES: usize = 4;
+
+/// Number of message words fed per lane between keystream generations
+const BATCH_BLOCK: usize = 16;
+
+/// MAC job over a message with lifetime `'a`
+pub trait MacJob<'a> {
+    /// Keystream Type
+    type Keystream: ZucKeystream;
+
+    /// Mac Word Type
+    type Word: MacWord;
+
+    /// load the ZUC core of the job without initialization
+    fn load(&self) -> Zuc;
+
+    /// create the MAC state of the job from the initialized keystream
+    fn start(&self, zuc: Self::Keystream) -> MacCore<Self::Keystream, Self::Word>;
+
+    /// get the message and its bit length
+    fn message(&self) -> (&'a [u8], usize);
+
+    /// finish the MAC state with the message tail and store the tag
+    fn complete(&mut self, core: MacCore<Self::Keystream, Self::Word>, tail: &[u8], bitlen: usize);
+}
+
+/// Compute the MACs of jobs in groups of [`BATCH_LANES`].
+///
+/// The keystream generators of a group are initialized in lockstep,
+/// and the keystream of the whole words shared by all messages is generated in lockstep.
+/// The rest of each message is processed by the job itself.
+pub fn compute_batch<'a, 'j, J>(jobs: impl IntoIterator<Item = &'j mut J>)
+where
+    J: MacJob<'a> + 'j,
+{
+    let word_size = size_of::<J::Word>();
+    let ks_per_word = word_size / 4;
+
+    let mut jobs = jobs.into_iter();
+    loop {
+        let mut group: [Option<&mut J>; BATCH_LANES] = array::from_fn(|_| jobs.next());
+        if group[0].is_none() {
+            break;
+        }
+
+        let msgs: [(&'a [u8], usize); BATCH_LANES] =
+            array::from_fn(|i| match group[i].as_deref() {
+                Some(job) => job.message(),
+                None => (&[][..], 0),
+            });
+
+        let mut common = usize::MAX;
+        for i in 0..BATCH_LANES {
+            if group[i].is_some() {
+                let (msg, bitlen) = msgs[i];
+                assert!(bitlen <= msg.len() * 8);
+                common = common.min(bitlen / (word_size * 8));
+            }
+        }
+
+        // unused lanes run on zeroed cores
+        let zucs: [Zuc; BATCH_LANES] = array::from_fn(|i| match group[i].as_deref() {
+            Some(job) => job.load(),
+            None => Zuc::zeroed(),
+        });
+        let mut zucs = Interleaved::<J::Keystream, BATCH_LANES>::init(zucs)
+            .into_lanes()
+            .into_iter();
+        let mut cores: [_; BATCH_LANES] = array::from_fn(|i| {
+            let zuc = zucs.next()?;
+            group[i].as_deref().map(|job| job.start(zuc))
+        });
+        let start: [u64; BATCH_LANES] =
+            array::from_fn(|i| cores[i].as_ref().map_or(0, |core| core.zuc.position()));
+        let mut spare: [J::Keystream; BATCH_LANES] =
+            array::from_fn(|_| J::Keystream::from_core(Zuc::zeroed()));
+
+        let mut keystream = Zeroizing::new([[0_u32; BATCH_BLOCK * 4]; BATCH_LANES]);
+        let mut done = 0;
+        while done < common {
+            let n = (common - done).min(BATCH_BLOCK);
+
+            {
+                let mut cores = cores.iter_mut();
+                let mut spare = spare.iter_mut();
+                let mut lanes: [&mut J::Keystream; BATCH_LANES] =
+                    array::from_fn(|_| match cores.next() {
+                        Some(Some(core)) => &mut core.zuc,
+                        _ => spare.next().unwrap(),
+                    });
+                for k in 0..n * ks_per_word {
+                    let words = ZucKeystream::generate_lanes(&mut lanes);
+                    for i in 0..BATCH_LANES {
+                        keystream[i][k] = words[i];
+                    }
+                }
+            }
+
+            for i in 0..BATCH_LANES {
+                if let Some(core) = cores[i].as_mut() {
+                    let msg = &msgs[i].0[done * word_size..(done + n) * word_size];
+                    core.update_words(msg, &keystream[i][..n * ks_per_word]);
+                }
+            }
+
+            done += n;
+        }
+        for zuc in &mut spare {
+            zuc.core_mut().zeroize();
+        }
+
+        for i in 0..BATCH_LANES {
+            if let (Some(job), Some(core)) = (group[i].as_deref_mut(), cores[i].take()) {
+                let (msg, bitlen) = msgs[i];
+                let done = common * word_size;
+                debug_assert_eq!(core.zuc.position() - start[i], (done / 4) as u64);
+                job.complete(core, &msg[done..], bitlen - done * 8);
+            }
+        }
+    }
+}
@@ -0,0 +1,226 @@
+//! Multi-threaded algorithms
+
+use super::keystream::{Keystream, SliceKeystream};
+use super::mac::BATCH_LANES;
+use super::mac::{compute_batch, keystream_len, MacCore, MacJob, MacKeyPair, MacProfile, MacWord};
+use super::stream_cipher::{xor_batch, XorJob};
+
+use alloc::vec::Vec;
+use core::mem::size_of;
+
+use rayon::prelude::*;
+use stdx::default::default;
+use zeroize::Zeroizing;
+
+/// Number of message bytes accumulated by a task
+const PARALLEL_CHUNK: usize = 64 * 1024;
+
+/// Compute the MAC of a message on multiple threads
+///
+/// This backs the `compute_parallel` methods of [`Zuc128Mac`](crate::zuc128::Zuc128Mac),
+/// [`Eia3Mac`](crate::eia3::Eia3Mac) and [`Zuc256Mac`](crate::zuc256::Zuc256Mac).
+///
+/// The keystream is generated serially into a buffer of about the message size, which is zeroized afterwards.
+/// The tag is linear in the message bits,
+/// so the whole message words are split into chunks, each chunk is accumulated from a zero tag
+/// with the key pair at its start on the rayon thread pool, and the partial tags are combined before the tail is fed.
+/// It is worthwhile for messages of several megabytes.
+///
+/// # Panics
+/// + Panics if `bitlen` is greater than the bit length of `msg`.
+pub fn compute_parallel<T: MacWord>(
+    mut zuc: impl Keystream,
+    msg: &[u8],
+    bitlen: usize,
+    profile: MacProfile,
+) -> T {
+    assert!(bitlen <= msg.len() * 8);
+
+    let len = keystream_len::<T>(bitlen, profile);
+    let keystream: Zeroizing<Vec<u32>> = Zeroizing::new((0..len).map(|_| zuc.next_key()).collect());
+
+    let n = size_of::<T>() / 4;
+    let (tag, keystream) = match profile {
+        MacProfile::Zuc128 => (T::default(), &keystream[..]),
+        MacProfile::Zuc256 => {
+            let (tag, rest) = keystream.split_at(n);
+            (T::gen_word(&mut SliceKeystream::new(tag)), rest)
+        }
+    };
+
+    // the core starting at message word `i`
+    let core_at = |i: usize, tag: T| {
+        let key = T::KeyPair::gen_key_pair(&mut SliceKeystream::new(&keystream[i * n..]));
+        MacCore {
+            zuc: SliceKeystream::new(&keystream[(i + 2) * n..]),
+            key,
+            tag,
+            rem: default(),
+            cnt: 0,
+            buf: default(),
+        }
+    };
+
+    let words = bitlen / (size_of::<T>() * 8);
+    let body = &msg[..words * size_of::<T>()];
+    let partial = body
+        .par_chunks(PARALLEL_CHUNK)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let mut core = core_at(i * PARALLEL_CHUNK / size_of::<T>(), T::default());
+            core.update(chunk);
+            core.tag
+        })
+        .reduce(T::default, |mut a, b| {
+            a ^= b;
+            a
+        });
+
+    let mut tag = tag;
+    tag ^= partial;
+    let mut core = core_at(words, tag);
+    core.finalize(&msg[body.len()..], bitlen - body.len() * 8, profile)
+}
+
+/// Apply the keystreams of jobs on multiple threads
+///
+/// Each task processes a group of [`BATCH_LANES`] jobs with [`xor_batch`].
+pub fn xor_batch_parallel<J: XorJob + Send>(jobs: &mut [J]) {
+    jobs.par_chunks_mut(BATCH_LANES)
+        .for_each(|jobs| xor_batch(jobs));
+}
+
+/// Compute the MACs of jobs on multiple threads
+///
+/// Each task processes a group of [`BATCH_LANES`] jobs with [`compute_batch`].
+pub fn compute_batch_parallel<'a, J: MacJob<'a> + Send>(jobs: &mut [J]) {
+    jobs.par_chunks_mut(BATCH_LANES)
+        .for_each(|jobs| compute_batch(jobs));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::eia3::Eia3Mac;
+    use crate::internal::mac::MacTag;
+    use crate::zuc128::Zuc128Mac;
+    use crate::zuc256::Zuc256Mac;
+
+    use alloc::vec;
+    use numeric_cast::TruncatingCast;
+
+    fn message() -> Vec<u8> {
+        (0..PARALLEL_CHUNK * 3 + 1000)
+            .map(|i| (i * 31 + i / 7).truncating_cast::<u8>())
+            .collect()
+    }
+
+    #[test]
+    fn zuc128() {
+        let msg = message();
+        let (ik, iv) = ([0x2f; 16], [0x71; 16]);
+        for bitlen in [
+            0,
+            31,
+            32,
+            PARALLEL_CHUNK * 8,
+            PARALLEL_CHUNK * 8 + 33,
+            msg.len() * 8 - 5,
+        ] {
+            let expected = Zuc128Mac::compute(&ik, &iv, &msg, bitlen);
+            assert_eq!(
+                Zuc128Mac::compute_parallel(&ik, &iv, &msg, bitlen),
+                expected
+            );
+        }
+
+        let bitlen = msg.len() * 8 - 3;
+        let expected = Eia3Mac::compute(0x1234_5678, 0x15, 1, &ik, &msg, bitlen);
+        let tag = Eia3Mac::compute_parallel(0x1234_5678, 0x15, 1, &ik, &msg, bitlen);
+        assert_eq!(tag, expected);
+    }
+
+    #[test]
+    fn zuc256() {
+        fn check<T: MacTag>(msg: &[u8]) {
+            let (ik, iv) = ([0x5e; 32], [0x09; 23]);
+            for bitlen in [0, 127, PARALLEL_CHUNK * 8 + 200, msg.len() * 8] {
+                let expected = Zuc256Mac::<T>::compute(&ik, &iv, msg, bitlen);
+                assert_eq!(
+                    Zuc256Mac::<T>::compute_parallel(&ik, &iv, msg, bitlen),
+                    expected
+                );
+            }
+        }
+
+        let msg = message();
+        check::<u32>(&msg);
+        check::<u64>(&msg);
+        check::<u128>(&msg);
+    }
+
+    #[test]
+    fn batch() {
+        use crate::eea3::{eea3_xor_inplace, eea3_xor_inplace_parallel, Eea3XorJob};
+        use crate::eia3::Eia3MacJob;
+        use crate::zuc128::Zuc128MacJob;
+        use crate::zuc128::{zuc128_xor_inplace, zuc128_xor_inplace_parallel, Zuc128XorJob};
+        use crate::zuc256::Zuc256MacJob;
+
+        let msg: Vec<u8> = message().into_iter().take(1000).collect();
+        let bitlens: Vec<usize> = (0..10).map(|i| i * 797 % (msg.len() * 8)).collect();
+        let keys: Vec<[u8; 16]> = (0..10_u8).map(|i| [i; 16]).collect();
+
+        let mut data = vec![msg.clone(); 10];
+        let mut jobs: Vec<_> = (data.iter_mut().zip(&keys).zip(&bitlens))
+            .map(|((data, key), &bitlen)| Zuc128XorJob::new(key, key, data, bitlen))
+            .collect();
+        zuc128_xor_inplace_parallel(&mut jobs);
+        for (i, data) in data.iter().enumerate() {
+            let mut expected = msg.clone();
+            zuc128_xor_inplace(&keys[i], &keys[i], &mut expected, bitlens[i]);
+            assert_eq!(*data, expected);
+        }
+
+        let mut data = vec![msg.clone(); 10];
+        let mut jobs: Vec<_> = (data.iter_mut().zip(&keys).zip(&bitlens))
+            .map(|((data, key), &bitlen)| Eea3XorJob::new(7, 3, 1, key, data, bitlen))
+            .collect();
+        eea3_xor_inplace_parallel(&mut jobs);
+        for (i, data) in data.iter().enumerate() {
+            let mut expected = msg.clone();
+            eea3_xor_inplace(7, 3, 1, &keys[i], &mut expected, bitlens[i]);
+            assert_eq!(*data, expected);
+        }
+
+        let mut jobs: Vec<_> = (keys.iter().zip(&bitlens))
+            .map(|(key, &bitlen)| Zuc128MacJob::new(key, key, &msg, bitlen))
+            .collect();
+        Zuc128Mac::compute_batch_parallel(&mut jobs);
+        for (i, job) in jobs.iter().enumerate() {
+            let expected = Zuc128Mac::compute(&keys[i], &keys[i], &msg, bitlens[i]);
+            assert_eq!(job.tag, Some(expected));
+        }
+
+        let mut jobs: Vec<_> = (keys.iter().zip(&bitlens))
+            .map(|(key, &bitlen)| Eia3MacJob::new(7, 3, 1, key, &msg, bitlen))
+            .collect();
+        Eia3Mac::compute_batch_parallel(&mut jobs);
+        for (i, job) in jobs.iter().enumerate() {
+            let expected = Eia3Mac::compute(7, 3, 1, &keys[i], &msg, bitlens[i]);
+            assert_eq!(job.tag, Some(expected));
+        }
+
+        let (ik, iv) = ([0x5e; 32], [0x09; 23]);
+        let mut jobs: Vec<_> = bitlens
+            .iter()
+            .map(|&bitlen| Zuc256MacJob::<u64>::new(&ik, &iv, &msg, bitlen))
+            .collect();
+        Zuc256Mac::compute_batch_parallel(&mut jobs);
+        for (i, job) in jobs.iter().enumerate() {
+            let expected = Zuc256Mac::<u64>::compute(&ik, &iv, &msg, bitlens[i]);
+            assert_eq!(job.tag, Some(expected));
+        }
+    }
+}
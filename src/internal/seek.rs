@@ -0,0 +1,337 @@
+//! Keystream position and state checkpoints
+
+use super::keystream::ZucKeystream;
+use super::zuc::Zuc;
+
+/// Default spacing between checkpoints, in words
+pub const DEFAULT_CHECKPOINT_SPACING: u64 = 1024;
+
+/// Position in a ZUC keystream
+///
+/// It counts the 32-bit words generated since initialization.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    /// number of words
+    words: u64,
+}
+
+impl Position {
+    /// Create a position from a number of 32-bit words
+    #[must_use]
+    pub const fn from_words(words: u64) -> Self {
+        Self { words }
+    }
+
+    /// Number of 32-bit words
+    #[must_use]
+    pub const fn words(self) -> u64 {
+        self.words
+    }
+
+    /// Number of bytes
+    #[must_use]
+    pub const fn bytes(self) -> u64 {
+        self.words * 4
+    }
+
+    /// Number of bits
+    #[must_use]
+    pub const fn bits(self) -> u64 {
+        self.words * 32
+    }
+}
+
+/// Number of checkpoints kept besides the initial state
+pub const CHECKPOINTS: usize = 8;
+
+/// States of a keystream to seek from
+///
+/// ZUC moves one word at a time, forward or backward,
+/// so seeking far away restarts from the nearest stored state at or before the target when it is closer.
+/// A checkpoint is stored whenever the position passes a multiple of the spacing,
+/// and the latest [`CHECKPOINTS`] checkpoints are kept besides the initial state.
+#[derive(Clone, Debug)]
+pub struct Checkpoints {
+    /// initial state and its position
+    origin: (u64, Zuc),
+    /// latest checkpoints and their positions, the oldest is overwritten first
+    ring: [Option<(u64, Zuc)>; CHECKPOINTS],
+    /// slot of the next checkpoint
+    head: usize,
+    /// position of the next checkpoint
+    next: u64,
+    /// spacing between checkpoints, in words
+    spacing: u64,
+}
+
+impl Checkpoints {
+    /// Start with the current state of `zuc` as the initial state
+    pub fn new(zuc: &impl ZucKeystream) -> Self {
+        let pos = zuc.position();
+        Self {
+            origin: (pos, zuc.core().clone()),
+            ring: Default::default(),
+            head: 0,
+            next: (pos / DEFAULT_CHECKPOINT_SPACING + 1) * DEFAULT_CHECKPOINT_SPACING,
+            spacing: DEFAULT_CHECKPOINT_SPACING,
+        }
+    }
+
+    /// Set the spacing between checkpoints, in words
+    ///
+    /// The stored checkpoints are kept.
+    pub fn set_spacing(&mut self, spacing: u64, pos: u64) {
+        assert!(spacing > 0, "checkpoint spacing must be positive");
+        self.spacing = spacing;
+        self.next = pos.div_ceil(spacing) * spacing;
+    }
+
+    /// Generate the next word of `zuc`, storing a checkpoint before it when it is due
+    #[inline(always)]
+    pub fn generate(&mut self, zuc: &mut impl ZucKeystream) -> u32 {
+        let pos = zuc.position();
+        if pos == self.next {
+            self.store(pos, zuc.core());
+            self.next += self.spacing;
+        }
+        zuc.next_key()
+    }
+
+    /// Store a checkpoint, unless the state at `pos` is already stored
+    fn store(&mut self, pos: u64, core: &Zuc) {
+        if self.ring.iter().flatten().any(|(p, _)| *p == pos) {
+            return;
+        }
+        self.ring[self.head] = Some((pos, core.clone()));
+        self.head = (self.head + 1) % CHECKPOINTS;
+    }
+
+    /// Find the nearest stored state at or before `target`
+    fn nearest(&self, target: u64) -> Option<&(u64, Zuc)> {
+        core::iter::once(&self.origin)
+            .chain(self.ring.iter().flatten())
+            .filter(|(pos, _)| *pos <= target)
+            .max_by_key(|(pos, _)| *pos)
+    }
+
+    /// Move `zuc` to the position `target`
+    pub fn seek<K: ZucKeystream>(&mut self, zuc: &mut K, target: u64) {
+        // restore the nearest stored state at or before the target,
+        // if it is closer than the current state in either direction
+        if let Some((pos, state)) = self.nearest(target) {
+            if target - pos < zuc.position().abs_diff(target) {
+                *zuc = K::resume(state.clone(), *pos);
+            }
+        }
+        self.next = zuc.position().div_ceil(self.spacing) * self.spacing;
+
+        while zuc.position() < target {
+            let _ = self.generate(zuc);
+        }
+        while zuc.position() > target {
+            let _ = zuc.step_back();
+        }
+        self.next = zuc.position().div_ceil(self.spacing) * self.spacing;
+    }
+}
+
+/// Implements a seekable keystream generator `$name { zuc: $keystream, checkpoints: Checkpoints }`
+macro_rules! impl_seekable {
+    ($name:ident, $keystream:ty) => {
+        impl $name {
+            /// Wraps a keystream generator, whose current state becomes the initial state to seek from
+            #[must_use]
+            pub fn new(zuc: $keystream) -> Self {
+                let checkpoints = $crate::internal::seek::Checkpoints::new(&zuc);
+                Self { zuc, checkpoints }
+            }
+
+            /// Returns the number of words generated since initialization
+            #[must_use]
+            pub fn position(&self) -> $crate::Position {
+                self.zuc.position()
+            }
+
+            /// Sets the spacing between the state checkpoints used for seeking backward, in 32-bit words
+            ///
+            /// Smaller spacing makes seeking backward faster, at the cost of more frequent state copies.
+            ///
+            /// # Panics
+            /// + Panics if `words` is zero.
+            pub fn set_checkpoint_spacing(&mut self, words: u64) {
+                let pos = self.zuc.position().words();
+                self.checkpoints.set_spacing(words, pos);
+            }
+
+            /// Returns the wrapped keystream generator
+            #[must_use]
+            pub fn get_ref(&self) -> &$keystream {
+                &self.zuc
+            }
+
+            /// Unwraps the keystream generator
+            #[must_use]
+            pub fn into_inner(self) -> $keystream {
+                self.zuc
+            }
+        }
+
+        impl From<$keystream> for $name {
+            fn from(zuc: $keystream) -> Self {
+                Self::new(zuc)
+            }
+        }
+
+        impl cipher::AlgorithmName for $name {
+            fn write_alg_name(f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                <$keystream as cipher::AlgorithmName>::write_alg_name(f)
+            }
+        }
+
+        impl cipher::KeySizeUser for $name {
+            type KeySize = <$keystream as cipher::KeySizeUser>::KeySize;
+        }
+
+        impl cipher::IvSizeUser for $name {
+            type IvSize = <$keystream as cipher::IvSizeUser>::IvSize;
+        }
+
+        impl cipher::BlockSizeUser for $name {
+            type BlockSize = cipher::typenum::U4;
+        }
+
+        impl cipher::ParBlocksSizeUser for $name {
+            type ParBlocksSize = cipher::typenum::U1;
+        }
+
+        impl cipher::StreamBackend for $name {
+            fn gen_ks_block(&mut self, block: &mut cipher::Block<Self>) {
+                let z = self.checkpoints.generate(&mut self.zuc);
+                block.copy_from_slice(&z.to_be_bytes());
+            }
+        }
+
+        impl cipher::StreamCipherSeekCore for $name {
+            type Counter = u64;
+
+            fn get_block_pos(&self) -> u64 {
+                self.zuc.position().words()
+            }
+
+            fn set_block_pos(&mut self, pos: u64) {
+                self.checkpoints.seek(&mut self.zuc, pos);
+            }
+        }
+
+        impl cipher::StreamCipherCore for $name {
+            fn remaining_blocks(&self) -> Option<usize> {
+                None
+            }
+
+            fn process_with_backend(
+                &mut self,
+                f: impl cipher::StreamClosure<BlockSize = Self::BlockSize>,
+            ) {
+                f.call(self);
+            }
+        }
+    };
+}
+
+pub(crate) use impl_seekable;
+
+#[cfg(test)]
+mod tests {
+    use super::{Checkpoints, CHECKPOINTS};
+
+    use crate::internal::keystream::ZucKeystream;
+    use crate::zuc128::{Zuc128Keystream, Zuc128SeekableKeystream, Zuc128SeekableStreamCipher};
+
+    use cipher::{StreamCipher, StreamCipherSeek};
+
+    fn expected(k: &[u8; 16], iv: &[u8; 16]) -> [u32; 300] {
+        let mut zuc = Zuc128Keystream::new(k, iv);
+        core::array::from_fn(|_| zuc.generate())
+    }
+
+    /// seeks to the word at `target` and checks it
+    fn check(cipher: &mut Zuc128SeekableStreamCipher, target: usize, expected: u32) {
+        let mut word = [0; 4];
+        cipher.seek(target * 4);
+        cipher.apply_keystream(&mut word);
+        assert_eq!(u32::from_be_bytes(word), expected);
+        assert_eq!(cipher.current_pos::<usize>(), target * 4 + 4);
+    }
+
+    #[test]
+    fn seek() {
+        let k = [0x3d; 16];
+        let iv = [0x84; 16];
+        let expected = expected(&k, &iv);
+
+        for spacing in [1, 7, 64, 1000] {
+            let mut zuc = Zuc128SeekableKeystream::new(Zuc128Keystream::new(&k, &iv));
+            zuc.set_checkpoint_spacing(spacing);
+            let mut cipher = Zuc128SeekableStreamCipher::from_core(zuc);
+
+            for target in [0_usize, 5, 250, 3, 100, 64, 299, 0, 128, 127] {
+                check(&mut cipher, target, expected[target]);
+            }
+        }
+    }
+
+    #[test]
+    fn seek_resumed() {
+        let k = [0x5e; 16];
+        let iv = [0x17; 16];
+        let expected = expected(&k, &iv);
+
+        // the initial state of a resumed keystream is not at position 0
+        let mut zuc = Zuc128Keystream::new(&k, &iv);
+        for _ in 0..200 {
+            let _ = zuc.generate();
+        }
+        let zuc = Zuc128Keystream::import_state(&zuc.export_state()).unwrap();
+        let mut zuc = Zuc128SeekableKeystream::new(zuc);
+        zuc.set_checkpoint_spacing(64);
+        let mut cipher = Zuc128SeekableStreamCipher::from_core(zuc);
+
+        for target in [150_usize, 250, 0, 299, 199, 200, 260, 10] {
+            check(&mut cipher, target, expected[target]);
+        }
+    }
+
+    #[test]
+    fn nearest() {
+        let mut zuc = Zuc128Keystream::new(&[0x42; 16], &[0x24; 16]);
+        let mut checkpoints = Checkpoints::new(&zuc);
+        checkpoints.set_spacing(10, 0);
+        for _ in 0..200 {
+            let _ = checkpoints.generate(&mut zuc);
+        }
+
+        // the latest checkpoints are kept besides the initial state
+        let oldest = 200 - CHECKPOINTS as u64 * 10;
+        for (target, expected) in [
+            (199, 190),
+            (155, 150),
+            (oldest + 5, oldest),
+            (oldest - 1, 0),
+        ] {
+            let (pos, state) = checkpoints.nearest(target).unwrap();
+            assert_eq!(*pos, expected);
+
+            let mut resumed = Zuc128Keystream::resume(state.clone(), *pos);
+            let mut scalar = Zuc128Keystream::new(&[0x42; 16], &[0x24; 16]);
+            for _ in 0..*pos {
+                let _ = scalar.generate();
+            }
+            assert_eq!(resumed.generate(), scalar.generate());
+        }
+
+        // passing a stored checkpoint again does not evict the others
+        checkpoints.seek(&mut zuc, 0);
+        checkpoints.seek(&mut zuc, 200);
+        assert_eq!(checkpoints.nearest(oldest).unwrap().0, oldest);
+    }
+}
@@ -1,15 +1,30 @@
-use super::keystream::Keystream;
+use super::keystream::{xor_word, Interleaved, Keystream, ZucKeystream};
+use super::mac::BATCH_LANES;
+use super::zuc::Zuc;
+
+use core::array;
 
 use stdx::slice::SliceExt as _;
+use zeroize::Zeroize;
+
+pub fn xor_inplace(zuc: &mut impl ZucKeystream, data: &mut [u8], bitlen: usize) {
+    let start = zuc.position();
+
+    xor_eea3(zuc, data, bitlen);
 
-pub fn xor_inplace(zuc: &mut impl Keystream<Word = u32>, data: &mut [u8], bitlen: usize) {
+    // one word per 4 bytes, plus the word of the partial tail
+    debug_assert_eq!(zuc.position() - start, (data.len() / 4 + 1) as u64);
+}
+
+/// XOR the first `bitlen` bits of `data` with the keystream, as in 128-EEA3
+///
+/// The whole of `data` is XOR-ed, then the bits after `bitlen` are cleared,
+/// except the byte at `bitlen / 8` when `bitlen` is a multiple of 8.
+pub fn xor_eea3(zuc: &mut impl Keystream, data: &mut [u8], bitlen: usize) {
     assert!(bitlen <= data.len() * 8);
 
     for chunk in data.as_chunks_mut_::<4>().0 {
-        let k = zuc.next_key().to_be_bytes();
-        for i in 0..4 {
-            chunk[i] ^= k[i];
-        }
+        xor_word(chunk, zuc.next_key());
     }
 
     {
@@ -28,3 +43,74 @@ pub fn xor_inplace(zuc: &mut impl Keystream<Word = u32>, data: &mut [u8], bitlen
         data[i] = 0;
     }
 }
+
+/// XOR job over a bitstream
+pub trait XorJob {
+    /// Keystream Type
+    type Keystream: ZucKeystream;
+
+    /// load the ZUC core of the job without initialization
+    fn load(&self) -> Zuc;
+
+    /// get the bitstream and its bit length
+    fn data(&mut self) -> (&mut [u8], usize);
+}
+
+/// Apply the keystreams of jobs in groups of [`BATCH_LANES`].
+///
+/// The keystream generators of a group are initialized in lockstep,
+/// and the keystream of the whole words shared by all bitstreams is generated in lockstep.
+pub fn xor_batch<'j, J>(jobs: impl IntoIterator<Item = &'j mut J>)
+where
+    J: XorJob + 'j,
+{
+    let mut jobs = jobs.into_iter();
+    loop {
+        let mut group: [Option<&mut J>; BATCH_LANES] = array::from_fn(|_| jobs.next());
+        if group[0].is_none() {
+            break;
+        }
+
+        // unused lanes run on zeroed cores
+        let zucs: [Zuc; BATCH_LANES] = array::from_fn(|i| match group[i].as_deref() {
+            Some(job) => job.load(),
+            None => Zuc::zeroed(),
+        });
+
+        let mut lanes = group.iter_mut();
+        let mut bufs: [_; BATCH_LANES] = array::from_fn(|_| {
+            let job = lanes.next().and_then(Option::as_deref_mut)?;
+            let (data, bitlen) = job.data();
+            assert!(bitlen <= data.len() * 8);
+            Some((data, bitlen))
+        });
+
+        let common = bufs
+            .iter()
+            .flatten()
+            .map(|&(_, bitlen)| bitlen / 32)
+            .min()
+            .unwrap_or(0);
+
+        let mut lanes = Interleaved::<J::Keystream, BATCH_LANES>::init(zucs);
+        for w in 0..common {
+            let words = lanes.generate();
+            for i in 0..BATCH_LANES {
+                if let Some((data, _)) = bufs[i].as_mut() {
+                    let chunk = &mut data.as_chunks_mut_::<4>().0[w];
+                    xor_word(chunk, words[i]);
+                }
+            }
+        }
+
+        for (mut zuc, buf) in lanes.into_lanes().into_iter().zip(bufs) {
+            match buf {
+                Some((data, bitlen)) => {
+                    let done = common * 4;
+                    xor_inplace(&mut zuc, &mut data[done..], bitlen - done * 8);
+                }
+                None => zuc.core_mut().zeroize(),
+            }
+        }
+    }
+}
@@ -0,0 +1,172 @@
+//! Key window of 32-bit words
+
+use super::keystream::Keystream;
+use super::mac::MacKeyPair;
+
+use core::array;
+use core::ops::ShlAssign;
+
+use numeric_cast::TruncatingCast;
+
+/// Key window of `N` 32-bit keystream words, most significant word first
+///
+/// It replaces the 64/128/256-bit key pairs on 32-bit targets,
+/// where wide integers are emulated with carries across registers.
+#[derive(Copy, Clone)]
+pub struct KeyWindow<const N: usize>(pub [u32; N]);
+
+impl<const N: usize> KeyWindow<N> {
+    /// Generate the window from the next `N` keystream words
+    #[inline(always)]
+    fn generate(zuc: &mut impl Keystream) -> Self {
+        Self(array::from_fn(|_| zuc.next_key()))
+    }
+}
+
+impl<const N: usize> ShlAssign<usize> for KeyWindow<N> {
+    #[inline(always)]
+    fn shl_assign(&mut self, rhs: usize) {
+        let (q, r) = (rhs / 32, rhs % 32);
+        let w = &mut self.0;
+        for i in 0..N {
+            let high = if i + q < N { w[i + q] } else { 0 };
+            let low = if i + q + 1 < N { w[i + q + 1] } else { 0 };
+            w[i] = if r == 0 {
+                high
+            } else {
+                (high << r) | (low >> (32 - r))
+            };
+        }
+    }
+}
+
+// key pair form 32 bit word
+impl MacKeyPair for KeyWindow<2> {
+    type Word = u32;
+
+    #[inline(always)]
+    fn gen_key_pair(zuc: &mut impl Keystream) -> Self {
+        Self::generate(zuc)
+    }
+
+    #[inline(always)]
+    fn high(&self) -> u32 {
+        self.0[0]
+    }
+
+    #[inline(always)]
+    fn low(&self) -> u32 {
+        self.0[1]
+    }
+
+    #[inline(always)]
+    fn set_low(&mut self, low: u32) {
+        self.0[1] = low;
+    }
+
+    #[cfg(feature = "small")]
+    fn words_mut(&mut self) -> &mut [u32] {
+        &mut self.0
+    }
+}
+
+// key pair form 64 bit word
+impl MacKeyPair for KeyWindow<4> {
+    type Word = u64;
+
+    #[inline(always)]
+    fn gen_key_pair(zuc: &mut impl Keystream) -> Self {
+        Self::generate(zuc)
+    }
+
+    #[inline(always)]
+    fn high(&self) -> u64 {
+        let w = &self.0;
+        (u64::from(w[0]) << 32) | u64::from(w[1])
+    }
+
+    #[inline(always)]
+    fn low(&self) -> u64 {
+        let w = &self.0;
+        (u64::from(w[2]) << 32) | u64::from(w[3])
+    }
+
+    #[inline(always)]
+    fn set_low(&mut self, low: u64) {
+        let w = &mut self.0;
+        w[2] = (low >> 32).truncating_cast::<u32>();
+        w[3] = low.truncating_cast::<u32>();
+    }
+
+    #[cfg(feature = "small")]
+    fn words_mut(&mut self) -> &mut [u32] {
+        &mut self.0
+    }
+}
+
+// key pair form 128 bit word
+impl MacKeyPair for KeyWindow<8> {
+    type Word = u128;
+
+    #[inline(always)]
+    fn gen_key_pair(zuc: &mut impl Keystream) -> Self {
+        Self::generate(zuc)
+    }
+
+    #[inline(always)]
+    fn high(&self) -> u128 {
+        let w = &self.0;
+        (u128::from(w[0]) << 96)
+            | (u128::from(w[1]) << 64)
+            | (u128::from(w[2]) << 32)
+            | u128::from(w[3])
+    }
+
+    #[inline(always)]
+    fn low(&self) -> u128 {
+        let w = &self.0;
+        (u128::from(w[4]) << 96)
+            | (u128::from(w[5]) << 64)
+            | (u128::from(w[6]) << 32)
+            | u128::from(w[7])
+    }
+
+    #[inline(always)]
+    fn set_low(&mut self, low: u128) {
+        let w = &mut self.0;
+        w[4] = (low >> 96).truncating_cast::<u32>();
+        w[5] = (low >> 64).truncating_cast::<u32>();
+        w[6] = (low >> 32).truncating_cast::<u32>();
+        w[7] = low.truncating_cast::<u32>();
+    }
+
+    #[cfg(feature = "small")]
+    fn words_mut(&mut self) -> &mut [u32] {
+        &mut self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shl() {
+        let x: u128 = 0x0123_4567_89ab_cdef_fedc_ba98_7654_3210;
+        for rhs in [0, 1, 7, 31, 32, 33, 64, 95, 127, 128, 200] {
+            let mut w = KeyWindow([
+                (x >> 96).truncating_cast::<u32>(),
+                (x >> 64).truncating_cast::<u32>(),
+                (x >> 32).truncating_cast::<u32>(),
+                x.truncating_cast::<u32>(),
+            ]);
+            w <<= rhs;
+            let expected = x.checked_shl(rhs.try_into().unwrap()).unwrap_or(0);
+            let actual = (u128::from(w.0[0]) << 96)
+                | (u128::from(w.0[1]) << 64)
+                | (u128::from(w.0[2]) << 32)
+                | u128::from(w.0[3]);
+            assert_eq!(actual, expected, "rhs = {rhs}");
+        }
+    }
+}
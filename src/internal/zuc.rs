@@ -1,9 +1,12 @@
 //! ZUC shared implementation
 
+use core::array;
+
 use numeric_cast::TruncatingCast;
+use zeroize::Zeroize;
 
 /// S0 box
-static S0: [u8; 256] = const_str::hex!([
+pub static S0: [u8; 256] = const_str::hex!([
     "3E 72 5B 47 CA E0 00 33 04 D1 54 98 09 B9 6D CB",
     "7B 1B F9 32 AF 9D 6A A5 B8 2D FC 1D 08 53 03 90",
     "4D 4E 84 99 E4 CE D9 91 DD B6 85 48 8B 29 6E AC",
@@ -23,7 +26,7 @@ static S0: [u8; 256] = const_str::hex!([
 ]);
 
 /// S1 box
-static S1: [u8; 256] = const_str::hex!([
+pub static S1: [u8; 256] = const_str::hex!([
     "55 C2 63 71 3B C8 47 86 9F 3C DA 5B 29 AA FD 77",
     "8C C5 94 0C A6 1A 13 00 E3 A8 16 72 40 F9 F8 42",
     "44 26 68 96 81 D9 45 3E 10 76 C6 A7 8B 39 43 E1",
@@ -42,6 +45,25 @@ static S1: [u8; 256] = const_str::hex!([
     "64 BE 85 9B 2F 59 8A D7 B0 25 AC AF 12 03 E2 F2",
 ]);
 
+/// Inverse of a S box
+const fn invert_sbox(sbox: &[u8; 256]) -> [u8; 256] {
+    let mut inv = [0; 256];
+    let mut i: u8 = 0;
+    loop {
+        inv[sbox[i as usize] as usize] = i;
+        if i == 255 {
+            break inv;
+        }
+        i += 1;
+    }
+}
+
+/// Inverse of S0 box
+static S0_INV: [u8; 256] = invert_sbox(&S0);
+
+/// Inverse of S1 box
+static S1_INV: [u8; 256] = invert_sbox(&S1);
+
 /// (a + b) mod (2^32)
 #[inline(always)]
 fn add(a: u32, b: u32) -> u32 {
@@ -50,22 +72,77 @@ fn add(a: u32, b: u32) -> u32 {
 
 /// rotate left
 #[inline(always)]
-fn rol(x: u32, n: u32) -> u32 {
+const fn rol(x: u32, n: u32) -> u32 {
     x.rotate_left(n)
 }
 
 /// L1 linear transform
 #[inline(always)]
-fn l1(x: u32) -> u32 {
+const fn l1(x: u32) -> u32 {
     x ^ rol(x, 2) ^ rol(x, 10) ^ rol(x, 18) ^ rol(x, 24)
 }
 
 /// L2 linear transform
 #[inline(always)]
-fn l2(x: u32) -> u32 {
+const fn l2(x: u32) -> u32 {
     x ^ rol(x, 8) ^ rol(x, 14) ^ rol(x, 22) ^ rol(x, 30)
 }
 
+/// Inverse of L1 linear transform
+///
+/// NOTE: L1 is a polynomial in the rotation `t` with `t^32 = 1`, and `L1^16 = 1`,
+/// so the inverse is `L1^15`, which expands to the rotations below.
+#[inline(always)]
+fn l1_inv(x: u32) -> u32 {
+    let y = x ^ rol(x, 2) ^ rol(x, 4) ^ rol(x, 8) ^ rol(x, 12) ^ rol(x, 14);
+    y ^ rol(x, 16) ^ rol(x, 18) ^ rol(x, 22) ^ rol(x, 24) ^ rol(x, 30)
+}
+
+/// Inverse of L2 linear transform
+///
+/// NOTE: `L2^16 = 1` as well, so the inverse is `L2^15`.
+#[inline(always)]
+fn l2_inv(x: u32) -> u32 {
+    let y = x ^ rol(x, 2) ^ rol(x, 8) ^ rol(x, 10) ^ rol(x, 14) ^ rol(x, 16);
+    y ^ rol(x, 18) ^ rol(x, 20) ^ rol(x, 24) ^ rol(x, 28) ^ rol(x, 30)
+}
+
+/// Byte-indexed tables of a linear transform
+///
+/// `L(x) = T[0][x0] ^ T[1][x1] ^ T[2][x2] ^ T[3][x3]` where `x0..x3` are the big-endian bytes of `x`.
+#[cfg(feature = "ttable")]
+macro_rules! l_tables {
+    ($l:ident) => {{
+        let mut t = [[0; 256]; 4];
+        let mut j = 0;
+        while j < 4 {
+            let mut v: u32 = 0;
+            while v < 256 {
+                t[j][v as usize] = $l(v << (24 - 8 * j));
+                v += 1;
+            }
+            j += 1;
+        }
+        t
+    }};
+}
+
+/// L1 tables
+#[cfg(feature = "ttable")]
+static L1_TABLES: [[u32; 256]; 4] = l_tables!(l1);
+
+/// L2 tables
+#[cfg(feature = "ttable")]
+static L2_TABLES: [[u32; 256]; 4] = l_tables!(l2);
+
+/// Table-driven linear transform
+#[cfg(feature = "ttable")]
+#[inline(always)]
+fn l_lookup(t: &[[u32; 256]; 4], x: u32) -> u32 {
+    let x = x.to_be_bytes();
+    t[0][x[0] as usize] ^ t[1][x[1] as usize] ^ t[2][x[2] as usize] ^ t[3][x[3] as usize]
+}
+
 /// S box transform
 #[inline(always)]
 fn sbox(x: u32) -> u32 {
@@ -79,6 +156,130 @@ fn sbox(x: u32) -> u32 {
     u32::from_be_bytes(y)
 }
 
+/// Inverse S box transform
+#[inline(always)]
+fn sbox_inv(x: u32) -> u32 {
+    let x = x.to_be_bytes();
+    let y = [
+        S0_INV[x[0] as usize],
+        S1_INV[x[1] as usize],
+        S0_INV[x[2] as usize],
+        S1_INV[x[3] as usize],
+    ];
+    u32::from_be_bytes(y)
+}
+
+/// S(L1(x))
+#[cfg(not(feature = "ttable"))]
+#[inline(always)]
+fn sl1(x: u32) -> u32 {
+    sbox(l1(x))
+}
+
+/// S(L2(x))
+#[cfg(not(feature = "ttable"))]
+#[inline(always)]
+fn sl2(x: u32) -> u32 {
+    sbox(l2(x))
+}
+
+/// S(L1(x)) with table-driven L1
+///
+/// The S box is applied after the linear transform and its output goes into a modular addition,
+/// so only the linear layer is merged into the tables.
+#[cfg(feature = "ttable")]
+#[inline(always)]
+fn sl1(x: u32) -> u32 {
+    sbox(l_lookup(&L1_TABLES, x))
+}
+
+/// S(L2(x)) with table-driven L2
+#[cfg(feature = "ttable")]
+#[inline(always)]
+fn sl2(x: u32) -> u32 {
+    sbox(l_lookup(&L2_TABLES, x))
+}
+
+/// The next LFSR word
+#[cfg(not(any(target_pointer_width = "32", feature = "word32")))]
+#[inline(always)]
+fn lfsr_feedback(s0: u32, s4: u32, s10: u32, s13: u32, s15: u32, u: u32) -> u32 {
+    // standard:
+    // v = (2^15 * s15 + 2^17 * s13 + 2^21 * s10 + 2^20 * s4 + (1+2^8) * s0) mod (2^31 - 1)
+    // s[16] = (v + u) mod (2^31 - 1)
+    // if s[16] == 0 { s[16] = 2^31 - 1 }
+    //
+    // equivalent to:
+    // NOTE: s[i] is a 31-bit word
+    // sum = 2^15 * s15 + 2^17 * s13 + 2^21 * s10 + 2^20 * s4 + (1+2^8) * s0 + u
+    // NOTE: sum <= 2^53 - 1
+    //
+    // NOTE: (2^31) * x + y ≡ x + y (mod (2^31 - 1))
+    // sum = (sum >> 31) + (sum % (1<<31))
+    // NOTE: sum <= (2^22 - 1) + (2^31 - 1) <= (2^32 - 2)
+    //
+    // sum = (sum >> 31) + (sum % (1<<31))
+    // NOTE: sum <= 0 + (2^31 - 1)
+    //       sum <= 1 + (2^31 - 2)
+    //       sum <= 2^31 - 1
+    //
+    // if sum == 2^31 - 1 {
+    //     (v + u) mod (2^31 - 1) == 0
+    //     s[16] = sum = 2^31 - 1
+    // } else {
+    //     (v + u) mod (2^31 - 1) == sum
+    //     s[16] = sum
+    // }
+    //
+    // equivalent to:
+    // s[16] = sum
+
+    let mut sum = u64::from(u);
+    sum += u64::from(s0);
+    sum += u64::from(s0) << 8;
+    sum += u64::from(s4) << 20;
+    sum += u64::from(s10) << 21;
+    sum += u64::from(s13) << 17;
+    sum += u64::from(s15) << 15;
+
+    sum = (sum >> 31) + (sum & ((1 << 31) - 1));
+    let mut sum = sum.truncating_cast::<u32>();
+    sum = (sum >> 31) + (sum & ((1 << 31) - 1));
+
+    sum
+}
+
+/// (a + b) mod (2^31 - 1)
+#[cfg(any(target_pointer_width = "32", feature = "word32"))]
+#[inline(always)]
+fn add31(a: u32, b: u32) -> u32 {
+    let c = a + b;
+    (c & 0x7FFF_FFFF) + (c >> 31)
+}
+
+/// (2^n * x) mod (2^31 - 1)
+#[cfg(any(target_pointer_width = "32", feature = "word32"))]
+#[inline(always)]
+fn rol31(x: u32, n: u32) -> u32 {
+    ((x << n) | (x >> (31 - n))) & 0x7FFF_FFFF
+}
+
+/// The next LFSR word with 32-bit arithmetic
+#[cfg(any(target_pointer_width = "32", feature = "word32"))]
+#[inline(always)]
+fn lfsr_feedback(s0: u32, s4: u32, s10: u32, s13: u32, s15: u32, u: u32) -> u32 {
+    // same sum as the 64-bit version, reduced term by term
+    // NOTE: a, b <= 2^31 - 1 implies add31(a, b) <= 2^31 - 1
+    // NOTE: add31(a, b) == 0 only if a == b == 0, as in the 64-bit version
+
+    let mut sum = add31(s0, rol31(s0, 8));
+    sum = add31(sum, rol31(s4, 20));
+    sum = add31(sum, rol31(s10, 21));
+    sum = add31(sum, rol31(s13, 17));
+    sum = add31(sum, rol31(s15, 15));
+    add31(sum, u)
+}
+
 /// ZUC keystream generator
 #[derive(Clone, Debug)]
 pub(crate) struct Zuc {
@@ -92,6 +293,14 @@ pub(crate) struct Zuc {
     pub r2: u32,
 }
 
+impl Zeroize for Zuc {
+    fn zeroize(&mut self) {
+        self.s.zeroize();
+        self.r1.zeroize();
+        self.r2.zeroize();
+    }
+}
+
 impl Zuc {
     /// Zero-initialized
     pub fn zeroed() -> Self {
@@ -103,6 +312,8 @@ impl Zuc {
     }
 
     /// Creates a ZUC keystream generator
+    #[cfg(feature = "small")]
+    #[inline(never)]
     pub fn init(&mut self) {
         for _ in 0..32 {
             let x = self.bit_reconstruction();
@@ -116,13 +327,41 @@ impl Zuc {
         }
     }
 
+    /// Creates a ZUC keystream generator
+    #[cfg(not(feature = "small"))]
+    pub fn init(&mut self) {
+        macro_rules! rounds {
+            ($($k:literal)*) => {$(
+                let x = self.bit_reconstruction_at::<$k>();
+                let w = self.f(x);
+                self.lfsr_at::<$k>(w >> 1);
+            )*};
+        }
+
+        // 16 rounds rotate the LFSR back to its original position
+        for _ in 0..2 {
+            rounds!(0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15);
+        }
+
+        {
+            let x = self.bit_reconstruction();
+            self.f(x);
+        }
+    }
+
     /// `BitReconstruction` function
     fn bit_reconstruction(&mut self) -> [u32; 4] {
-        let Self { s, .. } = self;
-        let x0 = ((s[15] & 0x7FFF_8000) << 1) | (s[14] & 0xFFFF);
-        let x1 = ((s[11] & 0xFFFF) << 16) | (s[9] >> 15);
-        let x2 = ((s[7] & 0xFFFF) << 16) | (s[5] >> 15);
-        let x3 = ((s[2] & 0xFFFF) << 16) | (s[0] >> 15);
+        self.bit_reconstruction_at::<0>()
+    }
+
+    /// `BitReconstruction` function on the LFSR rotated by `K` words
+    #[inline(always)]
+    fn bit_reconstruction_at<const K: usize>(&self) -> [u32; 4] {
+        let s = |i: usize| self.s[(K + i) % 16];
+        let x0 = ((s(15) & 0x7FFF_8000) << 1) | (s(14) & 0xFFFF);
+        let x1 = ((s(11) & 0xFFFF) << 16) | (s(9) >> 15);
+        let x2 = ((s(7) & 0xFFFF) << 16) | (s(5) >> 15);
+        let x3 = ((s(2) & 0xFFFF) << 16) | (s(0) >> 15);
         [x0, x1, x2, x3]
     }
 
@@ -133,8 +372,8 @@ impl Zuc {
         let w = add(x[0] ^ (*r1), *r2);
         let w1 = add(*r1, x[1]);
         let w2 = (*r2) ^ x[2];
-        *r1 = sbox(l1((w1 << 16) | (w2 >> 16)));
-        *r2 = sbox(l2((w2 << 16) | (w1 >> 16)));
+        *r1 = sl1((w1 << 16) | (w2 >> 16));
+        *r2 = sl2((w2 << 16) | (w1 >> 16));
 
         w
     }
@@ -143,51 +382,7 @@ impl Zuc {
     fn lfsr_with_initialization_mode(&mut self, u: u32) {
         let Self { s, .. } = self;
 
-        // standard:
-        // v = (2^15 * s[15] + 2^17 * s[13] + 2^21 * s[10] + 2^20 * s[4] + (1+2^8) * s[0]) mod (2^31 - 1)
-        // s[16] = (v + u) mod (2^31 - 1)
-        // if s[16] == 0 { s[16] = 2^31 - 1 }
-        //
-        // equivalent to:
-        // NOTE: s[i] is a 31-bit word
-        // sum = 2^15 * s[15] + 2^17 * s[13] + 2^21 * s[10] + 2^20 * s[4] + (1+2^8) * s[0] + u
-        // NOTE: sum <= 2^53 - 1
-        //
-        // NOTE: (2^31) * x + y ≡ x + y (mod (2^31 - 1))
-        // sum = (sum >> 31) + (sum % (1<<31))
-        // NOTE: sum <= (2^22 - 1) + (2^31 - 1) <= (2^32 - 2)
-        //
-        // sum = (sum >> 31) + (sum % (1<<31))
-        // NOTE: sum <= 0 + (2^31 - 1)
-        //       sum <= 1 + (2^31 - 2)
-        //       sum <= 2^31 - 1
-        //
-        // if sum == 2^31 - 1 {
-        //     (v + u) mod (2^31 - 1) == 0
-        //     s[16] = sum = 2^31 - 1
-        // } else {
-        //     (v + u) mod (2^31 - 1) == sum
-        //     s[16] = sum
-        // }
-        //
-        // equivalent to:
-        // s[16] = sum
-
-        let s16 = {
-            let mut sum = u64::from(u);
-            sum += u64::from(s[0]);
-            sum += u64::from(s[0]) << 8;
-            sum += u64::from(s[4]) << 20;
-            sum += u64::from(s[10]) << 21;
-            sum += u64::from(s[13]) << 17;
-            sum += u64::from(s[15]) << 15;
-
-            sum = (sum >> 31) + (sum & ((1 << 31) - 1));
-            let mut sum = sum.truncating_cast::<u32>();
-            sum = (sum >> 31) + (sum & ((1 << 31) - 1));
-
-            sum
-        };
+        let s16 = lfsr_feedback(s[0], s[4], s[10], s[13], s[15], u);
 
         for i in 0..15 {
             s[i] = s[i + 1];
@@ -195,15 +390,123 @@ impl Zuc {
         s[15] = s16;
     }
 
+    /// `LFSRWithInitialisationMode` function on the LFSR rotated by `K` words
+    ///
+    /// The new word replaces `s[0]` in place, so that no word is shifted.
+    #[cfg(not(feature = "small"))]
+    #[inline(always)]
+    fn lfsr_at<const K: usize>(&mut self, u: u32) {
+        let s = |i: usize| self.s[(K + i) % 16];
+        self.s[K] = lfsr_feedback(s(0), s(4), s(10), s(13), s(15), u);
+    }
+
     /// `LFSRWithWorkMode` function
     pub fn lfsr_with_work_mode(&mut self) {
         self.lfsr_with_initialization_mode(0);
     }
 
     /// Generates the next 32-bit word in ZUC keystream
+    #[cfg_attr(feature = "small", inline(never))]
     pub fn generate(&mut self) -> u32 {
         self.lfsr_with_work_mode();
         let x = self.bit_reconstruction();
         self.f(x) ^ x[3]
     }
+
+    /// Restores the state before the last [`Zuc::generate`] and returns the word it generated
+    pub fn step_back(&mut self) -> u32 {
+        // the LFSR is updated before BR, so BR of the current state is the one used by F
+        let x = self.bit_reconstruction();
+
+        // r1 = S(L1((w1 << 16) | (w2 >> 16)))
+        // r2 = S(L2((w2 << 16) | (w1 >> 16)))
+        let a = l1_inv(sbox_inv(self.r1));
+        let b = l2_inv(sbox_inv(self.r2));
+        let w1 = (b << 16) | (a >> 16);
+        let w2 = (a << 16) | (b >> 16);
+
+        // w1 = r1 + x1, w2 = r2 ^ x2
+        self.r1 = w1.wrapping_sub(x[1]);
+        self.r2 = w2 ^ x[2];
+        let z = add(x[0] ^ self.r1, self.r2) ^ x[3];
+
+        self.lfsr_step_back();
+        z
+    }
+
+    /// Inverse of `LFSRWithWorkMode` function
+    fn lfsr_step_back(&mut self) {
+        /// 2^31 - 1
+        const P: u64 = (1 << 31) - 1;
+        /// 257^(-1) mod (2^31 - 1)
+        const INV_257: u64 = 0x00FF_00FF;
+
+        let s = &mut self.s;
+        let s16 = s[15];
+        for i in (1..16).rev() {
+            s[i] = s[i - 1];
+        }
+
+        // s16 = (2^15 * s15 + 2^17 * s13 + 2^21 * s10 + 2^20 * s4 + 257 * s0) mod (2^31 - 1)
+        // NOTE: the words are in [1, 2^31 - 1], where 2^31 - 1 represents 0
+        let rest = lfsr_feedback(0, s[4], s[10], s[13], s[15], 0);
+        let v = (u64::from(s16) + P - u64::from(rest)) % P;
+        let s0 = (v * INV_257 % P).truncating_cast::<u32>();
+        s[0] = if s0 == 0 { 0x7FFF_FFFF } else { s0 };
+    }
+
+    /// Initializes independent ZUC keystream generators in lockstep
+    ///
+    /// Each stage is applied to all lanes before the next stage,
+    /// so that the CPU can overlap the dependency chains of different lanes.
+    #[cfg(not(feature = "small"))]
+    #[inline(always)]
+    pub fn init_lanes<const N: usize>(lanes: &mut [&mut Self; N]) {
+        macro_rules! rounds {
+            ($($k:literal)*) => {$(
+                let x: [_; N] = array::from_fn(|i| lanes[i].bit_reconstruction_at::<$k>());
+                let w: [_; N] = array::from_fn(|i| lanes[i].f(x[i]));
+                for i in 0..N {
+                    lanes[i].lfsr_at::<$k>(w[i] >> 1);
+                }
+            )*};
+        }
+
+        // 16 rounds rotate the LFSRs back to their original positions
+        for _ in 0..2 {
+            rounds!(0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15);
+        }
+
+        {
+            let x: [_; N] = array::from_fn(|i| lanes[i].bit_reconstruction());
+            for i in 0..N {
+                lanes[i].f(x[i]);
+            }
+        }
+    }
+
+    /// Initializes independent ZUC keystream generators one by one
+    #[cfg(feature = "small")]
+    pub fn init_lanes<const N: usize>(lanes: &mut [&mut Self; N]) {
+        for zuc in lanes {
+            zuc.init();
+        }
+    }
+
+    /// Generates the next 32-bit words of independent ZUC keystream generators one by one
+    #[cfg(feature = "small")]
+    pub fn generate_lanes<const N: usize>(lanes: &mut [&mut Self; N]) -> [u32; N] {
+        array::from_fn(|i| lanes[i].generate())
+    }
+
+    /// Generates the next 32-bit words of independent ZUC keystream generators in lockstep
+    #[cfg(not(feature = "small"))]
+    #[inline(always)]
+    pub fn generate_lanes<const N: usize>(lanes: &mut [&mut Self; N]) -> [u32; N] {
+        for zuc in lanes.iter_mut() {
+            zuc.lfsr_with_work_mode();
+        }
+        let x: [_; N] = array::from_fn(|i| lanes[i].bit_reconstruction());
+        array::from_fn(|i| lanes[i].f(x[i]) ^ x[i][3])
+    }
 }
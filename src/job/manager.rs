@@ -0,0 +1,391 @@
+use crate::eea3::Eea3XorJob;
+use crate::eia3::Eia3MacJob;
+use crate::internal::mac::{compute_batch, BATCH_LANES};
+use crate::internal::stream_cipher::xor_batch;
+use crate::zuc128::Zuc128XorJob;
+use crate::zuc256::Zuc256MacJob;
+
+use core::mem::{discriminant, Discriminant};
+
+/// Job of [`JobManager`]
+#[derive(Debug)]
+pub enum Job<'a> {
+    /// ZUC128 confidentiality job
+    Zuc128Xor(Zuc128XorJob<'a>),
+    /// 128-EEA3 job
+    Eea3Xor(Eea3XorJob<'a>),
+    /// 128-EIA3 job
+    Eia3Mac(Eia3MacJob<'a>),
+    /// ZUC256 MAC job with 32 bits MAC
+    Zuc256Mac32(Zuc256MacJob<'a, u32>),
+    /// ZUC256 MAC job with 64 bits MAC
+    Zuc256Mac64(Zuc256MacJob<'a, u64>),
+    /// ZUC256 MAC job with 128 bits MAC
+    Zuc256Mac128(Zuc256MacJob<'a, u128>),
+}
+
+impl<'a> From<Zuc128XorJob<'a>> for Job<'a> {
+    fn from(job: Zuc128XorJob<'a>) -> Self {
+        Job::Zuc128Xor(job)
+    }
+}
+
+impl<'a> From<Eea3XorJob<'a>> for Job<'a> {
+    fn from(job: Eea3XorJob<'a>) -> Self {
+        Job::Eea3Xor(job)
+    }
+}
+
+impl<'a> From<Eia3MacJob<'a>> for Job<'a> {
+    fn from(job: Eia3MacJob<'a>) -> Self {
+        Job::Eia3Mac(job)
+    }
+}
+
+impl<'a> From<Zuc256MacJob<'a, u32>> for Job<'a> {
+    fn from(job: Zuc256MacJob<'a, u32>) -> Self {
+        Job::Zuc256Mac32(job)
+    }
+}
+
+impl<'a> From<Zuc256MacJob<'a, u64>> for Job<'a> {
+    fn from(job: Zuc256MacJob<'a, u64>) -> Self {
+        Job::Zuc256Mac64(job)
+    }
+}
+
+impl<'a> From<Zuc256MacJob<'a, u128>> for Job<'a> {
+    fn from(job: Zuc256MacJob<'a, u128>) -> Self {
+        Job::Zuc256Mac128(job)
+    }
+}
+
+/// Number of job kinds, the variants of [`Job`]
+const KINDS: usize = 6;
+
+/// Number of jobs held by [`JobManager`]
+const CAPACITY: usize = BATCH_LANES * KINDS;
+
+/// queued job
+#[derive(Debug)]
+struct Slot<'a> {
+    job: Job<'a>,
+    done: bool,
+}
+
+/// Job manager with submit/flush semantics
+///
+/// Submitted jobs are queued until [`JobManager::LANES`] jobs of the same kind are pending,
+/// then those jobs are processed together by the batch algorithms.
+/// The queue holds [`JobManager::CAPACITY`] jobs, enough for a batch of every kind;
+/// when it is full, all pending jobs are processed.
+/// Completed jobs are returned in submission order.
+///
+/// The manager does not allocate, so it can be used in `no_std` environments.
+#[derive(Debug)]
+pub struct JobManager<'a> {
+    /// ring buffer of queued jobs
+    slots: [Option<Slot<'a>>; CAPACITY],
+    /// index of the oldest job
+    head: usize,
+    /// number of queued jobs
+    len: usize,
+}
+
+impl Default for JobManager<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> JobManager<'a> {
+    /// Number of jobs of the same kind processed together
+    pub const LANES: usize = BATCH_LANES;
+
+    /// Number of jobs held by the manager, including completed jobs which are not returned yet
+    pub const CAPACITY: usize = CAPACITY;
+
+    /// Create an empty job manager
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            slots: [const { None }; CAPACITY],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Number of queued jobs, including completed jobs which are not returned yet
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no job is queued
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Submit a job and return the oldest completed job if any
+    ///
+    /// The pending jobs of the same kind are processed when they fill a batch,
+    /// and all pending jobs are processed when the queue is full.
+    pub fn submit(&mut self, job: impl Into<Job<'a>>) -> Option<Job<'a>> {
+        // the queue always has a free slot after `submit` returns
+        debug_assert!(self.len < CAPACITY);
+
+        let job = job.into();
+        let kind = discriminant(&job);
+
+        let tail = (self.head + self.len) % CAPACITY;
+        self.slots[tail] = Some(Slot { job, done: false });
+        self.len += 1;
+
+        let pending = self
+            .slots
+            .iter()
+            .flatten()
+            .filter(|slot| !slot.done && discriminant(&slot.job) == kind)
+            .count();
+
+        if pending == BATCH_LANES {
+            self.process(Some(kind));
+        } else if self.len == CAPACITY {
+            self.process(None);
+        }
+
+        self.get_completed()
+    }
+
+    /// Return the oldest job if it is completed
+    pub fn get_completed(&mut self) -> Option<Job<'a>> {
+        if !self.is_oldest_done() {
+            return None;
+        }
+        let slot = self.slots[self.head].take()?;
+        self.head = (self.head + 1) % CAPACITY;
+        self.len -= 1;
+        Some(slot.job)
+    }
+
+    /// Process all pending jobs, even if they do not fill a batch,
+    /// and return the oldest completed job if any
+    ///
+    /// Call it repeatedly until it returns `None` to drain the manager.
+    pub fn flush(&mut self) -> Option<Job<'a>> {
+        if !self.is_oldest_done() {
+            self.process(None);
+        }
+        self.get_completed()
+    }
+
+    /// whether the oldest job is completed
+    fn is_oldest_done(&self) -> bool {
+        matches!(&self.slots[self.head], Some(slot) if slot.done)
+    }
+
+    /// process the pending jobs of `kind`, or of all kinds
+    fn process(&mut self, kind: Option<Discriminant<Job<'a>>>) {
+        macro_rules! pending {
+            ($kind:ident) => {
+                self.slots.iter_mut().flatten().filter_map(|slot| {
+                    if slot.done || kind.is_some_and(|kind| kind != discriminant(&slot.job)) {
+                        return None;
+                    }
+                    match &mut slot.job {
+                        Job::$kind(job) => {
+                            slot.done = true;
+                            Some(job)
+                        }
+                        _ => None,
+                    }
+                })
+            };
+        }
+
+        xor_batch(pending!(Zuc128Xor));
+        xor_batch(pending!(Eea3Xor));
+        compute_batch(pending!(Eia3Mac));
+        compute_batch(pending!(Zuc256Mac32));
+        compute_batch(pending!(Zuc256Mac64));
+        compute_batch(pending!(Zuc256Mac128));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::eea3::eea3_xor_inplace;
+    use crate::eia3::Eia3Mac;
+    use crate::zuc128::zuc128_xor_inplace;
+    use crate::zuc256::Zuc256Mac;
+
+    use numeric_cast::TruncatingCast;
+
+    #[test]
+    fn mixed() {
+        let ck = [0x17; 16];
+        let ik = [0x47; 16];
+        let k256 = [0xff; 32];
+        let iv256 = [0xff; 23];
+        let msg: [u8; 77] = core::array::from_fn(|i| (i * 13).truncating_cast::<u8>());
+
+        let mut bufs: [[u8; 77]; 12] = [msg; 12];
+        let mut bufs = bufs.iter_mut();
+
+        let mut mgr = JobManager::new();
+        let mut completed = [const { None }; 30];
+        let mut n = 0;
+
+        for i in 0..30_u32 {
+            let bitlen = 616 - (i as usize) * 13;
+            let job: Job<'_> = match i % 5 {
+                0 => Zuc128XorJob::new(&ck, &ik, bufs.next().unwrap(), bitlen).into(),
+                1 => Eea3XorJob::new(i, 3, 1, &ck, bufs.next().unwrap(), bitlen).into(),
+                2 => Eia3MacJob::new(i, 3, 1, &ik, &msg, bitlen).into(),
+                3 => Zuc256MacJob::<u64>::new(&k256, &iv256, &msg, bitlen).into(),
+                _ => Zuc256MacJob::<u128>::new(&k256, &iv256, &msg, bitlen).into(),
+            };
+            if let Some(job) = mgr.submit(job) {
+                completed[n] = Some(job);
+                n += 1;
+            }
+            assert!(mgr.len() < JobManager::CAPACITY);
+        }
+        while let Some(job) = mgr.flush() {
+            completed[n] = Some(job);
+            n += 1;
+        }
+        assert!(mgr.is_empty());
+        assert_eq!(n, 30);
+
+        for (i, job) in completed.into_iter().enumerate() {
+            let i = i.truncating_cast::<u32>();
+            let bitlen = 616 - (i as usize) * 13;
+            match job.unwrap() {
+                Job::Zuc128Xor(job) => {
+                    assert_eq!(i % 5, 0);
+                    let mut expected = msg;
+                    zuc128_xor_inplace(&ck, &ik, &mut expected, bitlen);
+                    assert_eq!(job.data, expected);
+                }
+                Job::Eea3Xor(job) => {
+                    assert_eq!(i % 5, 1);
+                    let mut expected = msg;
+                    eea3_xor_inplace(i, 3, 1, &ck, &mut expected, bitlen);
+                    assert_eq!(job.data, expected);
+                }
+                Job::Eia3Mac(job) => {
+                    assert_eq!(i % 5, 2);
+                    let expected = Eia3Mac::compute(i, 3, 1, &ik, &msg, bitlen);
+                    assert_eq!(job.tag, Some(expected));
+                }
+                Job::Zuc256Mac64(job) => {
+                    assert_eq!(i % 5, 3);
+                    let expected = Zuc256Mac::<u64>::compute(&k256, &iv256, &msg, bitlen);
+                    assert_eq!(job.tag, Some(expected));
+                }
+                Job::Zuc256Mac128(job) => {
+                    assert_eq!(i % 5, 4);
+                    let expected = Zuc256Mac::<u128>::compute(&k256, &iv256, &msg, bitlen);
+                    assert_eq!(job.tag, Some(expected));
+                }
+                Job::Zuc256Mac32(_) => unreachable!(),
+            }
+        }
+    }
+
+    #[test]
+    fn flush_partial() {
+        let ik = [0x47; 16];
+        let msg = [0x5a; 40];
+
+        let mut mgr = JobManager::new();
+        assert!(mgr.flush().is_none());
+
+        assert!(mgr
+            .submit(Eia3MacJob::new(1, 2, 0, &ik, &msg, 320))
+            .is_none());
+        assert!(mgr
+            .submit(Eia3MacJob::new(2, 2, 0, &ik, &msg, 319))
+            .is_none());
+        assert!(mgr.get_completed().is_none());
+        assert_eq!(mgr.len(), 2);
+
+        for (count, bitlen) in [(1, 320), (2, 319)] {
+            let Some(Job::Eia3Mac(job)) = mgr.flush() else {
+                panic!("expected a completed job")
+            };
+            assert_eq!(job.count, count);
+            assert_eq!(
+                job.tag,
+                Some(Eia3Mac::compute(count, 2, 0, &ik, &msg, bitlen))
+            );
+        }
+        assert!(mgr.flush().is_none());
+    }
+
+    #[test]
+    fn batches_per_kind() {
+        let ik = [0x47; 16];
+        let k256 = [0xff; 32];
+        let iv256 = [0xff; 23];
+        let msg = [0x5a; 40];
+
+        // a job is identified by its bit length
+        let eia3 = |bitlen| Job::from(Eia3MacJob::new(1, 2, 0, &ik, &msg, bitlen));
+        let zuc256 = |bitlen| Job::from(Zuc256MacJob::<u32>::new(&k256, &iv256, &msg, bitlen));
+        let bitlen = |job: &Job<'_>| match job {
+            Job::Eia3Mac(job) => job.bitlen,
+            Job::Zuc256Mac32(job) => job.bitlen,
+            _ => unreachable!(),
+        };
+
+        let mut mgr = JobManager::new();
+        let mut completed = [0; 8];
+        let mut n = 0;
+
+        // the 4th EIA3 job fills a batch, while the ZUC256 job before it is still pending
+        for (i, is_eia3) in [true, false, true, true, true, false, false, false]
+            .into_iter()
+            .enumerate()
+        {
+            let job = if is_eia3 {
+                eia3(300 + i)
+            } else {
+                zuc256(300 + i)
+            };
+            if let Some(job) = mgr.submit(job) {
+                completed[n] = bitlen(&job);
+                n += 1;
+            }
+            match i {
+                4 => assert_eq!(completed[..n], [300]),
+                7 => assert_eq!(completed[..n], [300, 301]),
+                _ => {}
+            }
+        }
+        assert_eq!(mgr.len(), 6);
+
+        // the other jobs are already completed and are returned in submission order
+        while let Some(job) = mgr.get_completed() {
+            match &job {
+                Job::Eia3Mac(job) => {
+                    let expected = Eia3Mac::compute(1, 2, 0, &ik, &msg, job.bitlen);
+                    assert_eq!(job.tag, Some(expected));
+                }
+                Job::Zuc256Mac32(job) => {
+                    let expected = Zuc256Mac::<u32>::compute(&k256, &iv256, &msg, job.bitlen);
+                    assert_eq!(job.tag, Some(expected));
+                }
+                _ => unreachable!(),
+            }
+            completed[n] = bitlen(&job);
+            n += 1;
+        }
+        assert!(mgr.is_empty());
+        assert_eq!(completed, core::array::from_fn(|i| 300 + i));
+    }
+}
@@ -18,12 +18,25 @@
 // ---
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "std")]
+extern crate std;
+
 mod internal {
+    pub mod bitslice;
+    #[cfg(not(any(target_pointer_width = "32", feature = "word32")))]
     pub mod u256;
+    #[cfg(any(target_pointer_width = "32", feature = "word32"))]
+    pub mod window;
     pub mod zuc;
 
     pub mod keystream;
     pub mod mac;
+    #[cfg(feature = "rayon")]
+    pub mod parallel;
+    pub mod seek;
     pub mod stream_cipher;
 }
 
@@ -31,37 +44,64 @@ pub mod zuc128 {
     //! ZUC128 Algorithms
     //! ([GB/T 33133.1-2016](https://openstd.samr.gov.cn/bzgk/gb/newGbInfo?hcno=8C41A3AEECCA52B5C0011C8010CF0715))
 
+    mod bitslice;
+    mod interleave;
     mod keystream;
     mod mac;
     mod stream_cipher;
 
+    pub use self::bitslice::Zuc128BitslicedKeystream;
+    pub use self::interleave::Zuc128InterleavedKeystream;
     pub use self::keystream::Zuc128Keystream;
-    pub use self::mac::Zuc128Mac;
-    pub use self::stream_cipher::{zuc128_xor_inplace, Zuc128StreamCipher};
+    pub use self::mac::{Zuc128Mac, Zuc128MacJob};
+    #[cfg(feature = "rayon")]
+    pub use self::stream_cipher::zuc128_xor_inplace_parallel;
+    pub use self::stream_cipher::{zuc128_xor_inplace, zuc128_xor_inplace_batch};
+    pub use self::stream_cipher::{Zuc128SeekableKeystream, Zuc128SeekableStreamCipher};
+    pub use self::stream_cipher::{Zuc128StreamCipher, Zuc128XorJob};
 }
 
 pub mod zuc256 {
     //! ZUC256 Algorithms
     //! ([ZUC256-version1.1](http://www.is.cas.cn/ztzl2016/zouchongzhi/201801/W020180416526664982687.pdf))
 
+    #[cfg(feature = "aead")]
+    mod aead;
     mod keystream;
     mod mac;
+    #[cfg(feature = "aead")]
+    mod siv;
+    #[cfg(feature = "aead")]
+    mod stream_aead;
     mod stream_cipher;
 
+    #[cfg(feature = "aead")]
+    pub use self::aead::{Zuc256Aead, Zuc256Aead128, Zuc256Aead64};
     pub use self::keystream::Zuc256Keystream;
-    pub use self::mac::Zuc256Mac;
+    pub use self::mac::{Zuc256Mac, Zuc256MacJob};
+    #[cfg(feature = "aead")]
+    pub use self::siv::Zuc256Siv;
+    #[cfg(feature = "aead")]
+    pub use self::stream_aead::{Zuc256StreamAead, Zuc256StreamDecryptor, Zuc256StreamEncryptor};
     pub use self::stream_cipher::Zuc256StreamCipher;
+    pub use self::stream_cipher::{Zuc256SeekableKeystream, Zuc256SeekableStreamCipher};
 }
 
 pub mod eea3 {
     //! 128-EEA3 Algorithms
     //! ([EEA3-EIA3-specification](https://www.gsma.com/solutions-and-impact/technologies/security/wp-content/uploads/2019/05/EEA3_EIA3_specification_v1_8.pdf))
 
+    mod fused;
     mod keystream;
     mod stream_cipher;
 
+    pub use self::fused::{eea3_eia3_inplace, MacOrder};
     pub use self::keystream::Eea3Keystream;
-    pub use self::stream_cipher::{eea3_xor_inplace, Eea3StreamCipher};
+    #[cfg(feature = "rayon")]
+    pub use self::stream_cipher::eea3_xor_inplace_parallel;
+    pub use self::stream_cipher::{eea3_xor_inplace, eea3_xor_inplace_batch};
+    pub use self::stream_cipher::{Eea3SeekableKeystream, Eea3SeekableStreamCipher};
+    pub use self::stream_cipher::{Eea3StreamCipher, Eea3XorJob};
 }
 
 pub mod eia3 {
@@ -70,8 +110,82 @@ pub mod eia3 {
 
     mod mac;
 
-    pub use self::mac::Eia3Mac;
+    pub use self::mac::{Eia3Mac, Eia3MacJob};
+}
+
+pub mod mac {
+    //! Generic MAC
+    //!
+    //! The ZUC-style MAC construction over any [`Keystream`](crate::Keystream) and tag width,
+    //! for the MAC variants of other ZUC profiles.
+
+    mod generic;
+
+    pub use self::generic::{GenericMac, MacBuilder};
+    pub use crate::internal::mac::{MacProfile, MacTag};
 }
 
+pub mod job {
+    //! Job Manager
+    //!
+    //! Queues jobs of different algorithms and processes them in batches,
+    //! with submit/flush semantics similar to
+    //! [intel-ipsec-mb](https://github.com/intel/intel-ipsec-mb).
+
+    mod manager;
+
+    pub use self::manager::{Job, JobManager};
+}
+
+#[cfg(all(feature = "std", feature = "aead"))]
+pub mod container {
+    //! Encrypted Container
+    //!
+    //! A self-describing, versioned file format which encrypts and authenticates a payload in chunks
+    //! with [`Zuc256StreamAead`](crate::zuc256::Zuc256StreamAead). See [`Header`] for the layout.
+
+    mod format;
+
+    pub use self::format::{open, seal};
+    pub use self::format::{Algorithm, ContainerError, Header};
+    pub use self::format::{MAGIC, MAX_CHUNK_SIZE, NONCE_SIZE, VERSION};
+}
+
+#[cfg(feature = "std")]
+pub mod pool {
+    //! Keystream Pool
+    //!
+    //! Pre-generates keystreams on a worker thread for packets whose parameters are known ahead of time.
+    //! Generated keystreams are zeroized when they are dropped.
+
+    mod pregen;
+
+    pub use self::pregen::{KeystreamBuffer, KeystreamPool, KeystreamSource, PendingKeystream};
+}
+
+pub mod state {
+    //! State Export
+    //!
+    //! Versioned export and import of keystream and MAC states,
+    //! for checkpointing a long computation and resuming it later.
+    //! The encoded formats are documented on [`KeystreamState`] and [`MacState`].
+    //!
+    //! With the `serde` feature, the states implement `Serialize` and `Deserialize` as byte strings.
+    //!
+    //! # Security
+    //! An exported state allows anyone to compute the rest of the keystream or to forge the MAC.
+    //! Protect it as the key itself.
+
+    mod export;
+
+    pub(crate) use self::export::algorithm;
+    pub use self::export::{KeystreamState, MacState, StateError, VERSION};
+}
+
+pub use self::internal::keystream::Keystream;
+pub use self::internal::seek::Position;
+
+#[cfg(feature = "aead")]
+pub use aead;
 pub use cipher;
 pub use digest;
@@ -0,0 +1,97 @@
+use crate::internal::keystream::Keystream;
+use crate::internal::mac::{MacCore, MacProfile, MacTag};
+
+use core::marker::PhantomData;
+
+/// Builder of [`GenericMac`]
+///
+/// # Examples
+/// ```
+/// use zuc::mac::{MacBuilder, MacProfile};
+/// use zuc::zuc128::{Zuc128Keystream, Zuc128Mac};
+///
+/// let (ik, iv) = ([0x3d; 16], [0x84; 16]);
+/// let msg = b"hello";
+///
+/// let mut mac = MacBuilder::new(Zuc128Keystream::new(&ik, &iv))
+///     .profile(MacProfile::Zuc128)
+///     .build::<u32>();
+/// mac.update(msg);
+/// assert_eq!(mac.finish(&[], 0), Zuc128Mac::compute(&ik, &iv, msg, 40));
+/// ```
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct MacBuilder<K> {
+    /// keystream
+    keystream: K,
+    /// construction
+    profile: MacProfile,
+}
+
+impl<K: Keystream> MacBuilder<K> {
+    /// Starts a builder over an initialized keystream, with the [`MacProfile::Zuc256`] construction
+    pub fn new(keystream: K) -> Self {
+        Self {
+            keystream,
+            profile: MacProfile::Zuc256,
+        }
+    }
+
+    /// Sets the construction of the MAC
+    pub fn profile(mut self, profile: MacProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Builds a MAC generator with the tag type `T`
+    ///
+    /// It consumes the first keystream words as the initial tag and key.
+    pub fn build<T: MacTag>(self) -> GenericMac<K, T> {
+        GenericMac {
+            core: MacCore::new(self.keystream, self.profile),
+            profile: self.profile,
+            _tag: PhantomData,
+        }
+    }
+}
+
+/// ZUC-style MAC generator over any keystream
+///
+/// The tag type `T` is `u32`, `u64` or `u128`.
+/// With a matching keystream and profile, it computes the same tags as
+/// [`Zuc128Mac`](crate::zuc128::Zuc128Mac), [`Eia3Mac`](crate::eia3::Eia3Mac)
+/// and [`Zuc256Mac`](crate::zuc256::Zuc256Mac).
+#[derive(Clone)]
+pub struct GenericMac<K: Keystream, T: MacTag> {
+    /// mac core
+    core: MacCore<K, T>,
+    /// construction
+    profile: MacProfile,
+    /// tag type
+    _tag: PhantomData<T>,
+}
+
+impl<K: Keystream, T: MacTag> GenericMac<K, T> {
+    /// Update the MAC generator with the bytes of a message
+    pub fn update(&mut self, msg: &[u8]) {
+        self.core.update(msg);
+    }
+
+    /// Finish the MAC generation and return the MAC
+    ///
+    /// # Panics
+    /// + Panics if `bitlen` is greater than the bit length of `tail`.
+    #[must_use]
+    pub fn finish(mut self, tail: &[u8], bitlen: usize) -> T {
+        self.core.finalize(tail, bitlen, self.profile)
+    }
+
+    /// Forks the MAC generator after the message absorbed so far
+    #[must_use]
+    pub fn fork(&self) -> Self
+    where
+        K: Clone,
+    {
+        self.clone()
+    }
+}
@@ -0,0 +1,347 @@
+use crate::eea3::Eea3Keystream;
+use crate::internal::keystream::{Keystream, SliceKeystream};
+use crate::internal::stream_cipher::xor_eea3;
+use crate::zuc256::Zuc256Keystream;
+
+use std::fmt;
+use std::sync::mpsc;
+use std::thread;
+use std::vec::Vec;
+
+use zeroize::Zeroize;
+
+/// Parameters of a pre-generated keystream
+///
+/// The keys are zeroized when the source is dropped.
+#[derive(Clone)]
+pub enum KeystreamSource {
+    /// 128-EEA3 keystream
+    Eea3 {
+        /// counter
+        count: u32,
+        /// carrier layer identification
+        bearer: u8,
+        /// transmission direction identification
+        direction: u8,
+        /// confidentiality key
+        ck: [u8; 16],
+    },
+    /// ZUC256 keystream
+    Zuc256 {
+        /// key
+        k: [u8; 32],
+        /// initial vector
+        iv: [u8; 23],
+    },
+}
+
+impl KeystreamSource {
+    /// Generates `n` keystream words
+    fn generate(&self, n: usize) -> Vec<u32> {
+        fn take(mut zuc: impl Keystream, n: usize) -> Vec<u32> {
+            (0..n).map(|_| zuc.next_key()).collect()
+        }
+
+        match self {
+            KeystreamSource::Eea3 {
+                count,
+                bearer,
+                direction,
+                ck,
+            } => take(Eea3Keystream::new(*count, *bearer, *direction, ck), n),
+            KeystreamSource::Zuc256 { k, iv } => take(Zuc256Keystream::new(k, iv), n),
+        }
+    }
+}
+
+impl Drop for KeystreamSource {
+    fn drop(&mut self) {
+        match self {
+            KeystreamSource::Eea3 { ck, .. } => ck.zeroize(),
+            KeystreamSource::Zuc256 { k, iv } => {
+                k.zeroize();
+                iv.zeroize();
+            }
+        }
+    }
+}
+
+impl fmt::Debug for KeystreamSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeystreamSource::Eea3 {
+                count,
+                bearer,
+                direction,
+                ..
+            } => f
+                .debug_struct("Eea3")
+                .field("count", count)
+                .field("bearer", bearer)
+                .field("direction", direction)
+                .finish_non_exhaustive(),
+            KeystreamSource::Zuc256 { .. } => f.debug_struct("Zuc256").finish_non_exhaustive(),
+        }
+    }
+}
+
+/// Pre-generated keystream for a bitstream of up to [`len`](Self::len) bytes
+///
+/// The keystream is zeroized when the buffer is dropped, whether it is used or not.
+pub struct KeystreamBuffer {
+    /// keystream words
+    words: Vec<u32>,
+    /// maximum byte length of the bitstream
+    len: usize,
+}
+
+impl KeystreamBuffer {
+    /// Returns the maximum byte length of the bitstream
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the buffer is for an empty bitstream
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// XORs the first `bitlen` bits of `data` with the keystream
+    ///
+    /// It computes the same output as [`eea3_xor_inplace`](crate::eea3::eea3_xor_inplace)
+    /// for a [`KeystreamSource::Eea3`] source.
+    /// The buffer is consumed, so a keystream is never applied twice.
+    ///
+    /// # Panics
+    /// + Panics if the length of `data` is greater than [`len`](Self::len).
+    /// + Panics if `bitlen` is greater than the bit length of `data`.
+    pub fn xor_inplace(self, data: &mut [u8], bitlen: usize) {
+        assert!(data.len() <= self.len, "keystream buffer is too short");
+        xor_eea3(&mut SliceKeystream::new(&self.words), data, bitlen);
+    }
+}
+
+impl Drop for KeystreamBuffer {
+    fn drop(&mut self) {
+        self.words.zeroize();
+    }
+}
+
+impl fmt::Debug for KeystreamBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeystreamBuffer")
+            .field("len", &self.len)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Keystream requested from [`KeystreamPool`]
+///
+/// Dropping it discards the keystream, which is zeroized.
+#[derive(Debug)]
+pub struct PendingKeystream {
+    /// receiver of the generated buffer
+    rx: mpsc::Receiver<KeystreamBuffer>,
+}
+
+impl PendingKeystream {
+    /// Returns the keystream if it is ready
+    #[must_use]
+    pub fn try_take(&self) -> Option<KeystreamBuffer> {
+        self.rx.try_recv().ok()
+    }
+
+    /// Waits for the keystream
+    ///
+    /// # Panics
+    /// + Panics if the worker thread has panicked.
+    #[must_use]
+    pub fn wait(self) -> KeystreamBuffer {
+        self.rx.recv().expect("keystream worker has stopped")
+    }
+}
+
+/// request sent to the worker
+struct Request {
+    /// keystream parameters
+    source: KeystreamSource,
+    /// maximum byte length of the bitstream
+    len: usize,
+    /// sender of the generated buffer
+    tx: mpsc::SyncSender<KeystreamBuffer>,
+}
+
+/// Keystream pre-generation on a worker thread
+///
+/// When the parameters of upcoming packets are known ahead of time,
+/// their keystreams can be generated in the background,
+/// so that encryption on the hot path is a plain XOR.
+/// Requests are processed in order.
+///
+/// # Examples
+/// ```
+/// use zuc::eea3::eea3_xor_inplace;
+/// use zuc::pool::{KeystreamPool, KeystreamSource};
+///
+/// let ck = [0x17; 16];
+/// let pool = KeystreamPool::new();
+/// let pending: Vec<_> = (0..4)
+///     .map(|count| {
+///         let source = KeystreamSource::Eea3 { count, bearer: 3, direction: 1, ck };
+///         pool.prepare(source, 1500)
+///     })
+///     .collect();
+///
+/// let mut expected = [0xa5; 100];
+/// eea3_xor_inplace(2, 3, 1, &ck, &mut expected, 800);
+///
+/// let mut packet = [0xa5; 100];
+/// let buffer = pending.into_iter().nth(2).unwrap().wait();
+/// buffer.xor_inplace(&mut packet, 800);
+/// assert_eq!(packet, expected);
+/// ```
+#[derive(Debug)]
+pub struct KeystreamPool {
+    /// sender of requests
+    tx: Option<mpsc::Sender<Request>>,
+    /// worker thread
+    worker: Option<thread::JoinHandle<()>>,
+}
+
+impl KeystreamPool {
+    /// Starts a pool with a worker thread
+    ///
+    /// # Panics
+    /// + Panics if the worker thread can not be spawned.
+    #[must_use]
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel::<Request>();
+        let worker = thread::Builder::new()
+            .name("zuc-keystream-pool".into())
+            .spawn(move || {
+                for req in rx {
+                    let n = req.len / 4 + 1;
+                    let buffer = KeystreamBuffer {
+                        words: req.source.generate(n),
+                        len: req.len,
+                    };
+                    // the buffer is zeroized on drop if the request has been discarded
+                    let _ = req.tx.send(buffer);
+                }
+            })
+            .expect("failed to spawn keystream worker");
+
+        Self {
+            tx: Some(tx),
+            worker: Some(worker),
+        }
+    }
+
+    /// Requests the keystream of a bitstream of up to `len` bytes
+    ///
+    /// # Panics
+    /// + Panics if the worker thread has panicked.
+    #[must_use]
+    pub fn prepare(&self, source: KeystreamSource, len: usize) -> PendingKeystream {
+        let (tx, rx) = mpsc::sync_channel(1);
+        let req = Request { source, len, tx };
+        let sender = self.tx.as_ref().expect("pool is running");
+        sender.send(req).expect("keystream worker has stopped");
+        PendingKeystream { rx }
+    }
+}
+
+impl Default for KeystreamPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for KeystreamPool {
+    fn drop(&mut self) {
+        drop(self.tx.take());
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::eea3::eea3_xor_inplace;
+    use crate::zuc256::Zuc256StreamCipher;
+
+    use cipher::{KeyIvInit, StreamCipher};
+
+    #[test]
+    fn eea3() {
+        let ck = [0x3c; 16];
+        let pool = KeystreamPool::new();
+        let pending: Vec<_> = (0..8)
+            .map(|count| {
+                let source = KeystreamSource::Eea3 {
+                    count,
+                    bearer: 0x15,
+                    direction: 1,
+                    ck,
+                };
+                pool.prepare(source, 40)
+            })
+            .collect();
+
+        for (count, pending) in (0..8).zip(pending) {
+            let bitlen = 13 + count as usize * 37;
+            let mut data = [0x5a; 40];
+            let mut expected = data;
+            eea3_xor_inplace(count, 0x15, 1, &ck, &mut expected, bitlen);
+
+            let buffer = pending.wait();
+            assert_eq!(buffer.len(), 40);
+            buffer.xor_inplace(&mut data, bitlen);
+            assert_eq!(data, expected);
+        }
+    }
+
+    #[test]
+    fn zuc256() {
+        let (k, iv) = ([0x61; 32], [0x1d; 23]);
+        let pool = KeystreamPool::new();
+        let pending = pool.prepare(KeystreamSource::Zuc256 { k, iv }, 64);
+
+        let mut expected = [0x33; 64];
+        Zuc256StreamCipher::new(&k.into(), &iv.into()).apply_keystream(&mut expected);
+
+        let mut data = [0x33; 64];
+        pending.wait().xor_inplace(&mut data, 64 * 8);
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn discard() {
+        let pool = KeystreamPool::new();
+        let source = || KeystreamSource::Zuc256 {
+            k: [0; 32],
+            iv: [0; 23],
+        };
+        drop(pool.prepare(source(), 1000));
+        let pending = pool.prepare(source(), 8);
+        drop(pool);
+        assert!(pending.try_take().is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "keystream buffer is too short")]
+    fn too_short() {
+        let pool = KeystreamPool::new();
+        let source = KeystreamSource::Zuc256 {
+            k: [0; 32],
+            iv: [0; 23],
+        };
+        let buffer = pool.prepare(source, 8).wait();
+        buffer.xor_inplace(&mut [0; 9], 72);
+    }
+}
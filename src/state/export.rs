@@ -0,0 +1,606 @@
+use crate::internal::keystream::{SliceKeystream, ZucKeystream};
+use crate::internal::mac::{MacCore, MacKeyPair, MacWord};
+use crate::internal::seek::Position;
+use crate::internal::zuc::Zuc;
+
+use core::array;
+use core::fmt;
+use core::mem::size_of;
+
+use numeric_cast::TruncatingCast;
+use stdx::default::default;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+/// Version of the encoded states
+pub const VERSION: u8 = 1;
+
+/// Algorithm identifiers of the encoded states
+pub(crate) mod algorithm {
+    pub const ZUC128: u8 = 1;
+    pub const ZUC256: u8 = 2;
+    pub const EEA3: u8 = 3;
+    pub const ZUC128_MAC: u8 = 4;
+    pub const EIA3: u8 = 5;
+    pub const ZUC256_MAC: u8 = 6;
+}
+
+/// Error of importing a state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum StateError {
+    /// The encoded state has a wrong length
+    InvalidLength,
+    /// The format version is not supported
+    UnsupportedVersion(u8),
+    /// The state belongs to another algorithm or MAC size
+    AlgorithmMismatch,
+    /// The state is malformed
+    InvalidState,
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidLength => write!(f, "invalid state length"),
+            Self::UnsupportedVersion(v) => write!(f, "unsupported state version: {v}"),
+            Self::AlgorithmMismatch => write!(f, "state algorithm mismatch"),
+            Self::InvalidState => write!(f, "invalid state"),
+        }
+    }
+}
+
+impl core::error::Error for StateError {}
+
+/// Exported state of a keystream generator
+///
+/// # Security
+/// The state contains the LFSR and FSM registers, from which the rest of the keystream can be computed.
+/// Treat it as secret as the key.
+///
+/// # Format
+/// [`to_bytes`](Self::to_bytes) encodes the state in [`LEN`](Self::LEN) bytes, integers in big endian.
+///
+/// | offset | size | field                                       |
+/// | ------ | ---- | ------------------------------------------- |
+/// | 0      | 1    | version, [`VERSION`]                        |
+/// | 1      | 1    | algorithm: 1 ZUC128, 2 ZUC256, 3 128-EEA3   |
+/// | 2      | 8    | position, in 32-bit words                   |
+/// | 10     | 64   | LFSR registers `s0..s15`, 31-bit each       |
+/// | 74     | 4    | FSM register `R1`                           |
+/// | 78     | 4    | FSM register `R2`                           |
+///
+/// The registers are zeroized when the state is dropped.
+#[derive(Clone)]
+pub struct KeystreamState {
+    /// algorithm identifier
+    algorithm: u8,
+    /// number of words generated since initialization
+    pos: u64,
+    /// zuc core
+    core: Zuc,
+}
+
+impl KeystreamState {
+    /// Length of the encoded state
+    pub const LEN: usize = 82;
+
+    /// Export the state of a keystream
+    pub(crate) fn export(algorithm: u8, zuc: &impl ZucKeystream) -> Self {
+        Self {
+            algorithm,
+            pos: zuc.position(),
+            core: zuc.core().clone(),
+        }
+    }
+
+    /// Import the state into a keystream
+    pub(crate) fn import<S: ZucKeystream>(&self, algorithm: u8) -> Result<S, StateError> {
+        if self.algorithm != algorithm {
+            return Err(StateError::AlgorithmMismatch);
+        }
+        Ok(S::resume(self.core.clone(), self.pos))
+    }
+
+    /// Returns the keystream position of the state
+    #[must_use]
+    pub fn position(&self) -> Position {
+        Position::from_words(self.pos)
+    }
+
+    /// Encode the state
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut buf = [0; Self::LEN];
+        buf[0] = VERSION;
+        buf[1] = self.algorithm;
+        buf[2..10].copy_from_slice(&self.pos.to_be_bytes());
+        for (chunk, s) in buf[10..74].chunks_exact_mut(4).zip(&self.core.s) {
+            chunk.copy_from_slice(&s.to_be_bytes());
+        }
+        buf[74..78].copy_from_slice(&self.core.r1.to_be_bytes());
+        buf[78..82].copy_from_slice(&self.core.r2.to_be_bytes());
+        buf
+    }
+
+    /// Decode the state
+    ///
+    /// # Errors
+    /// Returns an error if `bytes` is not a state encoded by [`to_bytes`](Self::to_bytes).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, StateError> {
+        let bytes: &[u8; Self::LEN] = bytes.try_into().map_err(|_| StateError::InvalidLength)?;
+        if bytes[0] != VERSION {
+            return Err(StateError::UnsupportedVersion(bytes[0]));
+        }
+
+        let algorithm = bytes[1];
+        if !matches!(
+            algorithm,
+            algorithm::ZUC128 | algorithm::ZUC256 | algorithm::EEA3
+        ) {
+            return Err(StateError::AlgorithmMismatch);
+        }
+
+        let word =
+            |i: usize| u32::from_be_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        let pos = u64::from_be_bytes(array::from_fn(|i| bytes[2 + i]));
+        let s: [u32; 16] = array::from_fn(|i| word(10 + i * 4));
+        if s.iter().any(|&s| s >> 31 != 0) {
+            return Err(StateError::InvalidState);
+        }
+
+        let core = Zuc {
+            s,
+            r1: word(74),
+            r2: word(78),
+        };
+        Ok(Self {
+            algorithm,
+            pos,
+            core,
+        })
+    }
+}
+
+impl Drop for KeystreamState {
+    fn drop(&mut self) {
+        self.core.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for KeystreamState {}
+
+impl fmt::Debug for KeystreamState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeystreamState")
+            .field("algorithm", &self.algorithm)
+            .field("pos", &self.pos)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Exported state of a MAC generator
+///
+/// # Security
+/// The state contains the keystream state and the key words of the MAC.
+/// Treat it as secret as the key.
+///
+/// # Format
+/// [`to_bytes`](Self::to_bytes) encodes the state in [`LEN`](Self::LEN) bytes, integers in big endian.
+/// Unused bytes of the fixed-size fields are zero.
+///
+/// | offset | size | field                                                       |
+/// | ------ | ---- | ----------------------------------------------------------- |
+/// | 0      | 1    | version, [`VERSION`]                                        |
+/// | 1      | 1    | algorithm: 4 ZUC128 MAC, 5 128-EIA3, 6 ZUC256 MAC           |
+/// | 2      | 1    | MAC size in bytes: 4, 8 or 16                               |
+/// | 3      | 1    | number of buffered message bytes                            |
+/// | 4      | 82   | keystream state, see [`KeystreamState`]                     |
+/// | 86     | 32   | key words, twice the MAC size                               |
+/// | 118    | 16   | intermediate tag, the MAC size                              |
+/// | 134    | 16   | buffered message bytes, less than the MAC size              |
+///
+/// The key words, the intermediate tag and the buffered bytes are zeroized when the state is dropped.
+#[derive(Clone)]
+pub struct MacState {
+    /// algorithm identifier
+    algorithm: u8,
+    /// MAC size in bytes
+    size: u8,
+    /// number of buffered message bytes
+    cnt: u8,
+    /// keystream state
+    keystream: KeystreamState,
+    /// key words
+    key: [u8; 32],
+    /// intermediate tag
+    tag: [u8; 16],
+    /// buffered message bytes
+    rem: [u8; 16],
+}
+
+impl MacState {
+    /// Length of the encoded state
+    pub const LEN: usize = 150;
+
+    /// Keystream algorithm of a MAC algorithm
+    fn keystream_algorithm(algorithm: u8) -> Option<u8> {
+        match algorithm {
+            algorithm::ZUC128_MAC | algorithm::EIA3 => Some(algorithm::ZUC128),
+            algorithm::ZUC256_MAC => Some(algorithm::ZUC256),
+            _ => None,
+        }
+    }
+
+    /// Export the state of a MAC core
+    pub(crate) fn export<S, T>(algorithm: u8, mac: &MacCore<S, T>) -> Self
+    where
+        S: ZucKeystream,
+        T: MacWord,
+    {
+        let n = size_of::<T>();
+        let Some(ks_algorithm) = Self::keystream_algorithm(algorithm) else {
+            unreachable!()
+        };
+
+        let mut key = [0; 32];
+        key[..n].copy_from_slice(&mac.key.high().to_be_array());
+        key[n..n * 2].copy_from_slice(&mac.key.low().to_be_array());
+        let mut tag = [0; 16];
+        tag[..n].copy_from_slice(&mac.tag.to_be_array());
+        let mut rem = [0; 16];
+        rem[..usize::from(mac.cnt)].copy_from_slice(&mac.rem[..usize::from(mac.cnt)]);
+
+        Self {
+            algorithm,
+            size: n.truncating_cast::<u8>(),
+            cnt: mac.cnt,
+            keystream: KeystreamState::export(ks_algorithm, &mac.zuc),
+            key,
+            tag,
+            rem,
+        }
+    }
+
+    /// Import the state into a MAC core
+    pub(crate) fn import<S, T>(&self, algorithm: u8) -> Result<MacCore<S, T>, StateError>
+    where
+        S: ZucKeystream,
+        T: MacWord,
+    {
+        let n = size_of::<T>();
+        if self.algorithm != algorithm || usize::from(self.size) != n {
+            return Err(StateError::AlgorithmMismatch);
+        }
+        let Some(ks_algorithm) = Self::keystream_algorithm(algorithm) else {
+            unreachable!()
+        };
+
+        let words: Zeroizing<[u32; 8]> = Zeroizing::new(array::from_fn(|i| {
+            let k = &self.key[i * 4..i * 4 + 4];
+            u32::from_be_bytes([k[0], k[1], k[2], k[3]])
+        }));
+        let key = T::KeyPair::gen_key_pair(&mut SliceKeystream::new(&words[..n / 2]));
+
+        let mut rem: cipher::generic_array::GenericArray<u8, T::ByteSize> = default();
+        rem.copy_from_slice(&self.rem[..n]);
+
+        Ok(MacCore {
+            zuc: self.keystream.import(ks_algorithm)?,
+            key,
+            tag: T::from_be_slice(&self.tag[..n]),
+            rem,
+            cnt: self.cnt,
+            buf: default(),
+        })
+    }
+
+    /// Returns the keystream position of the state
+    #[must_use]
+    pub fn position(&self) -> Position {
+        self.keystream.position()
+    }
+
+    /// Encode the state
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut buf = [0; Self::LEN];
+        buf[0] = VERSION;
+        buf[1] = self.algorithm;
+        buf[2] = self.size;
+        buf[3] = self.cnt;
+        buf[4..86].copy_from_slice(&*Zeroizing::new(self.keystream.to_bytes()));
+        buf[86..118].copy_from_slice(&self.key);
+        buf[118..134].copy_from_slice(&self.tag);
+        buf[134..150].copy_from_slice(&self.rem);
+        buf
+    }
+
+    /// Decode the state
+    ///
+    /// # Errors
+    /// Returns an error if `bytes` is not a state encoded by [`to_bytes`](Self::to_bytes).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, StateError> {
+        let bytes: &[u8; Self::LEN] = bytes.try_into().map_err(|_| StateError::InvalidLength)?;
+        if bytes[0] != VERSION {
+            return Err(StateError::UnsupportedVersion(bytes[0]));
+        }
+
+        let (algorithm, size, cnt) = (bytes[1], bytes[2], bytes[3]);
+        let sizes: &[u8] = match algorithm {
+            algorithm::ZUC128_MAC | algorithm::EIA3 => &[4],
+            algorithm::ZUC256_MAC => &[4, 8, 16],
+            _ => return Err(StateError::AlgorithmMismatch),
+        };
+        if !sizes.contains(&size) {
+            return Err(StateError::AlgorithmMismatch);
+        }
+        if cnt >= size {
+            return Err(StateError::InvalidState);
+        }
+
+        let keystream = KeystreamState::from_bytes(&bytes[4..86])?;
+        if Self::keystream_algorithm(algorithm) != Some(keystream.algorithm) {
+            return Err(StateError::AlgorithmMismatch);
+        }
+
+        let key: [u8; 32] = array::from_fn(|i| bytes[86 + i]);
+        let tag: [u8; 16] = array::from_fn(|i| bytes[118 + i]);
+        let rem: [u8; 16] = array::from_fn(|i| bytes[134 + i]);
+
+        let (size, cnt) = (usize::from(size), usize::from(cnt));
+        let padding = [&key[size * 2..], &tag[size..], &rem[cnt..]];
+        if padding.iter().any(|p| p.iter().any(|&b| b != 0)) {
+            return Err(StateError::InvalidState);
+        }
+
+        Ok(Self {
+            algorithm,
+            size: bytes[2],
+            cnt: bytes[3],
+            keystream,
+            key,
+            tag,
+            rem,
+        })
+    }
+}
+
+impl Drop for MacState {
+    fn drop(&mut self) {
+        self.key.zeroize();
+        self.tag.zeroize();
+        self.rem.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for MacState {}
+
+impl fmt::Debug for MacState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MacState")
+            .field("algorithm", &self.algorithm)
+            .field("size", &self.size)
+            .field("keystream", &self.keystream)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::{KeystreamState, MacState, StateError};
+
+    use core::fmt;
+    use core::marker::PhantomData;
+
+    use serde::de::{self, SeqAccess, Visitor};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use zeroize::Zeroizing;
+
+    /// States encoded as byte strings
+    trait Encoded: Sized {
+        const LEN: usize;
+        fn decode(bytes: &[u8]) -> Result<Self, StateError>;
+    }
+
+    impl Encoded for KeystreamState {
+        const LEN: usize = Self::LEN;
+        fn decode(bytes: &[u8]) -> Result<Self, StateError> {
+            Self::from_bytes(bytes)
+        }
+    }
+
+    impl Encoded for MacState {
+        const LEN: usize = Self::LEN;
+        fn decode(bytes: &[u8]) -> Result<Self, StateError> {
+            Self::from_bytes(bytes)
+        }
+    }
+
+    struct BytesVisitor<T>(PhantomData<T>);
+
+    impl<'de, T: Encoded> Visitor<'de> for BytesVisitor<T> {
+        type Value = T;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{} bytes of encoded state", T::LEN)
+        }
+
+        fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<T, E> {
+            T::decode(v).map_err(E::custom)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<T, A::Error> {
+            let mut buf = Zeroizing::new([0; MacState::LEN]);
+            let mut len = 0;
+            while let Some(b) = seq.next_element::<u8>()? {
+                if len == T::LEN {
+                    return Err(de::Error::invalid_length(len + 1, &self));
+                }
+                buf[len] = b;
+                len += 1;
+            }
+            T::decode(&buf[..len]).map_err(de::Error::custom)
+        }
+    }
+
+    impl Serialize for KeystreamState {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_bytes(&*Zeroizing::new(self.to_bytes()))
+        }
+    }
+
+    impl<'de> Deserialize<'de> for KeystreamState {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_bytes(BytesVisitor(PhantomData))
+        }
+    }
+
+    impl Serialize for MacState {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_bytes(&*Zeroizing::new(self.to_bytes()))
+        }
+    }
+
+    impl<'de> Deserialize<'de> for MacState {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_bytes(BytesVisitor(PhantomData))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::eia3::Eia3Mac;
+    use crate::zuc128::{Zuc128Keystream, Zuc128Mac};
+    use crate::zuc256::{Zuc256Keystream, Zuc256Mac};
+
+    #[test]
+    fn keystream_roundtrip() {
+        let mut zuc = Zuc256Keystream::new(&[0x5a; 32], &[0x3c; 23]);
+        for _ in 0..37 {
+            let _ = zuc.generate();
+        }
+
+        let bytes = zuc.export_state().to_bytes();
+        let state = KeystreamState::from_bytes(&bytes).unwrap();
+        assert_eq!(state.position().words(), 37);
+
+        let mut resumed = Zuc256Keystream::import_state(&state).unwrap();
+        for _ in 0..100 {
+            assert_eq!(resumed.generate(), zuc.generate());
+        }
+
+        // seeking back from a resumed state stops at its position
+        let mut expected = zuc.clone();
+        for _ in 0..5 {
+            let _ = resumed.step_back();
+            let _ = expected.step_back();
+        }
+        assert_eq!(resumed.position(), expected.position());
+        assert_eq!(resumed.generate(), expected.generate());
+    }
+
+    #[test]
+    fn mac_roundtrip() {
+        let msg: [u8; 100] = array::from_fn(|i| (i * 7).truncating_cast::<u8>());
+
+        let mut mac = Zuc256Mac::<u64>::new(&[0x11; 32], &[0x22; 23]);
+        mac.update(&msg[..21]);
+        let state = MacState::from_bytes(&mac.export_state().to_bytes()).unwrap();
+
+        let mut resumed = Zuc256Mac::<u64>::import_state(&state).unwrap();
+        resumed.update(&msg[21..]);
+        assert_eq!(
+            resumed.finish(&[0xff], 5),
+            Zuc256Mac::<u64>::compute(
+                &[0x11; 32],
+                &[0x22; 23],
+                &{
+                    let mut full = [0; 101];
+                    full[..100].copy_from_slice(&msg);
+                    full[100] = 0xff;
+                    full
+                },
+                805
+            )
+        );
+
+        let mut mac = Zuc128Mac::new(&[0x33; 16], &[0x44; 16]);
+        mac.update(&msg[..7]);
+        let mut resumed = Zuc128Mac::import_state(&mac.export_state()).unwrap();
+        resumed.update(&msg[7..]);
+        assert_eq!(
+            resumed.finish(&[], 0),
+            Zuc128Mac::compute(&[0x33; 16], &[0x44; 16], &msg, 800)
+        );
+
+        let mut mac = Eia3Mac::new(0x1234, 5, 1, &[0x55; 16]);
+        mac.update(&msg[..50]);
+        let mut resumed = Eia3Mac::import_state(&mac.export_state()).unwrap();
+        resumed.update(&msg[50..]);
+        assert_eq!(
+            resumed.finish(&[], 0),
+            Eia3Mac::compute(0x1234, 5, 1, &[0x55; 16], &msg, 800)
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let zuc = Zuc128Keystream::new(&[0; 16], &[0; 16]);
+        let state = zuc.export_state();
+        let bytes = state.to_bytes();
+
+        assert_eq!(
+            KeystreamState::from_bytes(&bytes[1..]).err(),
+            Some(StateError::InvalidLength)
+        );
+
+        let mut bad = bytes;
+        bad[0] = 2;
+        assert_eq!(
+            KeystreamState::from_bytes(&bad).err(),
+            Some(StateError::UnsupportedVersion(2))
+        );
+
+        let mut bad = bytes;
+        bad[10] = 0x80;
+        assert_eq!(
+            KeystreamState::from_bytes(&bad).err(),
+            Some(StateError::InvalidState)
+        );
+
+        assert_eq!(
+            Zuc256Keystream::import_state(&state).err(),
+            Some(StateError::AlgorithmMismatch)
+        );
+
+        let mac = Zuc256Mac::<u32>::new(&[0; 32], &[0; 23]);
+        let state = mac.export_state();
+        assert!(Zuc256Mac::<u128>::import_state(&state).is_err());
+        assert!(Zuc128Mac::import_state(&state).is_err());
+
+        let mut bad = state.to_bytes();
+        bad[3] = 4;
+        assert_eq!(
+            MacState::from_bytes(&bad).err(),
+            Some(StateError::InvalidState)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let mut mac = Eia3Mac::new(1, 2, 0, &[0x66; 16]);
+        mac.update(&[1, 2, 3]);
+        let state = mac.export_state();
+
+        let json = serde_json::to_string(&state).unwrap();
+        let decoded: MacState = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.to_bytes(), state.to_bytes());
+
+        let json = serde_json::to_string(&state.to_bytes()[..10]).unwrap();
+        assert!(serde_json::from_str::<MacState>(&json).is_err());
+    }
+}
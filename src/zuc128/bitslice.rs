@@ -0,0 +1,110 @@
+use super::Zuc128Keystream;
+
+use crate::internal::bitslice::{BitLanes, BitslicedZuc};
+
+use core::array;
+
+/// Bitsliced ZUC128 keystream generator
+/// ([GB/T 33133.1-2016](https://openstd.samr.gov.cn/bzgk/gb/newGbInfo?hcno=8C41A3AEECCA52B5C0011C8010CF0715))
+///
+/// Runs 32 (`L = u32`) or 64 (`L = u64`) independent ZUC128 instances
+/// in plain integer registers, without any table lookup.
+/// The execution time does not depend on keys, ivs or the keystream.
+///
+/// Lane `i` generates the same keystream as [`Zuc128Keystream`] with the `i`-th key and iv.
+#[derive(Debug, Clone)]
+pub struct Zuc128BitslicedKeystream<L: BitLanes = u64> {
+    /// bitsliced zuc core
+    core: BitslicedZuc<L>,
+    /// number of used lanes
+    lanes: usize,
+}
+
+impl<L: BitLanes> Zuc128BitslicedKeystream<L> {
+    /// Maximum number of lanes
+    pub const LANES: usize = L::LANES;
+
+    /// Creates a bitsliced ZUC128 keystream generator, one lane per key and iv
+    ///
+    /// # Panics
+    /// + If the lengths of `keys` and `ivs` are different.
+    /// + If there are more than [`Self::LANES`] keys.
+    #[must_use]
+    pub fn new(keys: &[[u8; 16]], ivs: &[[u8; 16]]) -> Self {
+        assert_eq!(keys.len(), ivs.len());
+        assert!(keys.len() <= L::LANES);
+
+        let zucs: [_; 64] = array::from_fn(|i| match (keys.get(i), ivs.get(i)) {
+            (Some(k), Some(iv)) => Zuc128Keystream::load(k, iv),
+            _ => Zuc128Keystream::load(&[0; 16], &[0; 16]),
+        });
+
+        let mut core = BitslicedZuc::load(&zucs[..L::LANES]);
+        core.init();
+        Self {
+            core,
+            lanes: keys.len(),
+        }
+    }
+
+    /// Number of used lanes
+    #[must_use]
+    pub fn lanes(&self) -> usize {
+        self.lanes
+    }
+
+    /// Generates the next 32-bit word of every used lane
+    ///
+    /// # Panics
+    /// If the length of `out` is not equal to [`Self::lanes`].
+    pub fn generate(&mut self, out: &mut [u32]) {
+        assert_eq!(out.len(), self.lanes);
+        self.core.generate(out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use numeric_cast::TruncatingCast;
+
+    fn check<L: BitLanes>(lanes: usize) {
+        let keys: [[u8; 16]; 64] =
+            array::from_fn(|i| array::from_fn(|j| (i * 31 + j * 7).truncating_cast::<u8>()));
+        let ivs: [[u8; 16]; 64] =
+            array::from_fn(|i| array::from_fn(|j| ((i * 5) ^ (j * 131)).truncating_cast::<u8>()));
+
+        let mut bitsliced = Zuc128BitslicedKeystream::<L>::new(&keys[..lanes], &ivs[..lanes]);
+        let mut scalar: [_; 64] = array::from_fn(|i| Zuc128Keystream::new(&keys[i], &ivs[i]));
+
+        let mut out = [0; 64];
+        for _ in 0..20 {
+            bitsliced.generate(&mut out[..lanes]);
+            for i in 0..lanes {
+                assert_eq!(out[i], scalar[i].generate(), "lane {i}");
+            }
+        }
+    }
+
+    #[test]
+    fn lanes() {
+        check::<u32>(32);
+        check::<u32>(5);
+        check::<u64>(64);
+        check::<u64>(33);
+        check::<u64>(0);
+    }
+
+    #[test]
+    fn examples() {
+        let keys = [[0; 16], [0xff; 16]];
+        let ivs = [[0; 16], [0xff; 16]];
+        let mut zuc = Zuc128BitslicedKeystream::<u64>::new(&keys, &ivs);
+        let mut out = [0; 2];
+        zuc.generate(&mut out);
+        assert_eq!(out, [0x27be_de74, 0x0657_cfa0]);
+        zuc.generate(&mut out);
+        assert_eq!(out, [0x0180_82da, 0x7096_398b]);
+    }
+}
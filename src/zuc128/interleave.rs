@@ -0,0 +1,102 @@
+use super::Zuc128Keystream;
+
+use crate::internal::keystream::Interleaved;
+
+use core::array;
+
+/// Interleaved ZUC128 keystream generators
+/// ([GB/T 33133.1-2016](https://openstd.samr.gov.cn/bzgk/gb/newGbInfo?hcno=8C41A3AEECCA52B5C0011C8010CF0715))
+///
+/// Advances `N` independent ZUC128 instances per call.
+/// The instances are updated in lockstep, so that the CPU can overlap their dependency chains
+/// without any SIMD instruction.
+///
+/// The batch algorithms, such as [`Zuc128Mac::compute_batch`](super::Zuc128Mac::compute_batch),
+/// run on the same lockstep generator, as the portable backend for targets without SIMD.
+#[derive(Debug, Clone)]
+pub struct Zuc128InterleavedKeystream<const N: usize>(Interleaved<Zuc128Keystream, N>);
+
+impl<const N: usize> Zuc128InterleavedKeystream<N> {
+    /// Creates `N` ZUC128 keystream generators, one per key and iv
+    #[must_use]
+    pub fn new(keys: &[[u8; 16]; N], ivs: &[[u8; 16]; N]) -> Self {
+        let cores = array::from_fn(|i| Zuc128Keystream::load(&keys[i], &ivs[i]));
+        Self(Interleaved::init(cores))
+    }
+
+    /// Interleaves existing ZUC128 keystream generators
+    #[must_use]
+    pub fn from_lanes(lanes: [Zuc128Keystream; N]) -> Self {
+        Self(Interleaved::from_lanes(lanes))
+    }
+
+    /// Splits into independent ZUC128 keystream generators
+    ///
+    /// Each generator keeps the position of its lane.
+    #[must_use]
+    pub fn into_lanes(self) -> [Zuc128Keystream; N] {
+        self.0.into_lanes()
+    }
+
+    /// Generates the next 32-bit word of every ZUC128 keystream
+    pub fn generate(&mut self) -> [u32; N] {
+        self.0.generate()
+    }
+}
+
+impl<const N: usize> Iterator for Zuc128InterleavedKeystream<N> {
+    type Item = [u32; N];
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.generate())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use numeric_cast::TruncatingCast;
+
+    #[test]
+    fn lanes() {
+        let keys: [[u8; 16]; 4] =
+            array::from_fn(|i| array::from_fn(|j| (i * 31 + j * 7).truncating_cast::<u8>()));
+        let ivs: [[u8; 16]; 4] =
+            array::from_fn(|i| array::from_fn(|j| (i * 5 + j * 131).truncating_cast::<u8>()));
+
+        let mut interleaved = Zuc128InterleavedKeystream::new(&keys, &ivs);
+        let mut scalar: [_; 4] = array::from_fn(|i| Zuc128Keystream::new(&keys[i], &ivs[i]));
+
+        for _ in 0..20 {
+            let words = interleaved.generate();
+            for i in 0..4 {
+                assert_eq!(words[i], scalar[i].generate());
+            }
+        }
+
+        let mut interleaved = Zuc128InterleavedKeystream::from_lanes(scalar.clone());
+        for _ in 0..20 {
+            let words = interleaved.generate();
+            for i in 0..4 {
+                assert_eq!(words[i], scalar[i].generate());
+            }
+        }
+
+        let lanes = interleaved.into_lanes();
+        for (mut a, mut b) in lanes.into_iter().zip(scalar) {
+            assert_eq!(a.position().words(), 40);
+            assert_eq!(a.position(), b.position());
+            assert_eq!(a.generate(), b.generate());
+
+            // the lanes keep their positions for stepping back
+            let word = a.generate();
+            assert_eq!(a.step_back(), word);
+            for _ in 0..41 {
+                let _ = a.step_back();
+            }
+            assert_eq!(a.position().words(), 0);
+        }
+    }
+}
@@ -1,5 +1,9 @@
-use crate::internal::keystream::Keystream;
+use core::array;
+
+use crate::internal::keystream::{Keystream, Sealed, ZucKeystream};
+use crate::internal::seek::Position;
 use crate::internal::zuc::Zuc;
+use crate::state::{algorithm, KeystreamState, StateError};
 
 /// (d<<8) constants
 static D: [u32; 16] = [
@@ -27,36 +31,114 @@ static D: [u32; 16] = [
 pub struct Zuc128Keystream {
     /// zuc core
     core: Zuc,
+    /// number of words generated since initialization
+    pos: u64,
 }
 
 impl Zuc128Keystream {
     /// Creates a ZUC128 keystream generator
     #[must_use]
     pub fn new(key: &[u8; 16], iv: &[u8; 16]) -> Self {
-        let mut zuc = Zuc::zeroed();
-        for i in 0..16 {
+        let mut zuc = Self::load(key, iv);
+        zuc.init();
+        Self::from_core(zuc)
+    }
+
+    /// Loads the key and iv into a ZUC core without initialization
+    pub(crate) fn load(key: &[u8; 16], iv: &[u8; 16]) -> Zuc {
+        let s = array::from_fn(|i| {
             let k_i = u32::from(key[i]);
             let iv_i = u32::from(iv[i]);
-            zuc.s[i] = (k_i << 23) | D[i] | iv_i;
-        }
-        zuc.init();
-        Self { core: zuc }
+            (k_i << 23) | D[i] | iv_i
+        });
+        Zuc { s, r1: 0, r2: 0 }
     }
 
     ///  Generates the next 32-bit word in ZUC128 keystream
     pub fn generate(&mut self) -> u32 {
+        self.pos += 1;
         self.core.generate()
     }
+
+    /// Returns the number of words generated since initialization
+    #[must_use]
+    pub fn position(&self) -> Position {
+        Position::from_words(self.pos)
+    }
+
+    /// Steps back by one word and returns the word generated last
+    ///
+    /// The next call to [`generate`](Self::generate) returns the same word again.
+    ///
+    /// # Panics
+    /// + Panics if no word has been generated.
+    pub fn step_back(&mut self) -> u32 {
+        assert!(self.pos > 0, "cannot step back before the initial state");
+        self.pos -= 1;
+        self.core.step_back()
+    }
+
+    /// Forks the keystream generator at its current position
+    #[must_use]
+    pub fn fork(&self) -> Self {
+        self.clone()
+    }
+
+    /// Exports the state of the keystream generator
+    ///
+    /// See [`KeystreamState`] for the format and the security notes.
+    #[must_use]
+    pub fn export_state(&self) -> KeystreamState {
+        KeystreamState::export(algorithm::ZUC128, self)
+    }
+
+    /// Creates a ZUC128 keystream generator from an exported state
+    ///
+    /// # Errors
+    /// Returns an error if the state is exported from another algorithm.
+    pub fn import_state(state: &KeystreamState) -> Result<Self, StateError> {
+        state.import(algorithm::ZUC128)
+    }
 }
 
-impl Keystream for Zuc128Keystream {
-    type Word = u32;
+impl Sealed for Zuc128Keystream {}
 
-    fn next_key(&mut self) -> Self::Word {
+impl Keystream for Zuc128Keystream {
+    fn next_key(&mut self) -> u32 {
         self.generate()
     }
 }
 
+impl ZucKeystream for Zuc128Keystream {
+    fn from_core(core: Zuc) -> Self {
+        Self { core, pos: 0 }
+    }
+
+    fn resume(core: Zuc, pos: u64) -> Self {
+        Self { core, pos }
+    }
+
+    fn core(&self) -> &Zuc {
+        &self.core
+    }
+
+    fn core_mut(&mut self) -> &mut Zuc {
+        &mut self.core
+    }
+
+    fn parts_mut(&mut self) -> (&mut Zuc, &mut u64) {
+        (&mut self.core, &mut self.pos)
+    }
+
+    fn position(&self) -> u64 {
+        self.pos
+    }
+
+    fn step_back(&mut self) -> u32 {
+        Self::step_back(self)
+    }
+}
+
 impl Iterator for Zuc128Keystream {
     type Item = u32;
 
@@ -299,4 +381,27 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn step_back() {
+        for Example { k, iv, .. } in ALL_EXAMPLES {
+            let mut zuc = Zuc128Keystream::new(k, iv);
+            let start = zuc.clone();
+
+            let words: [u32; 100] = core::array::from_fn(|_| zuc.generate());
+            for &z in words.iter().rev() {
+                assert_eq!(zuc.step_back(), z);
+            }
+            assert_eq!(zuc.core.s, start.core.s);
+            assert_eq!((zuc.core.r1, zuc.core.r2), (start.core.r1, start.core.r2));
+            assert_eq!(zuc.generate(), words[0]);
+        }
+    }
+
+    #[should_panic(expected = "cannot step back before the initial state")]
+    #[test]
+    fn step_back_at_start() {
+        let mut zuc = Zuc128Keystream::new(&[0; 16], &[0; 16]);
+        let _ = zuc.step_back();
+    }
 }
@@ -1,13 +1,17 @@
 use super::Zuc128Keystream;
 
-use crate::internal::mac::{MacCore, MacKeyPair, MacWord};
-
-use numeric_cast::TruncatingCast;
-use stdx::default::default;
+use crate::internal::mac::{
+    compute_batch, compute_with_keystream, keystream_len, MacCore, MacJob, MacProfile, MacWord,
+};
+#[cfg(feature = "rayon")]
+use crate::internal::parallel::{compute_batch_parallel, compute_parallel};
+use crate::internal::zuc::Zuc;
+use crate::state::{algorithm, MacState, StateError};
 
 /// ZUC128 MAC generator
 /// ([GB/T 33133.3-2021](http://c.gb688.cn/bzgk/gb/showGb?type=online&hcno=C6D60AE0A7578E970EF2280ABD49F4F0))
-pub struct Zuc128Mac(MacCore<Zuc128Keystream, u32>);
+#[derive(Clone)]
+pub struct Zuc128Mac(pub(crate) MacCore<Zuc128Keystream, u32>);
 
 impl Zuc128Mac {
     /// Compute the MAC of a message
@@ -27,19 +31,86 @@ impl Zuc128Mac {
         Self::new(ik, iv).finish(msg, bitlen)
     }
 
+    /// Compute the MACs of multiple messages
+    ///
+    /// The keystream generation of independent jobs is interleaved.
+    /// The MAC of each job is stored in [`Zuc128MacJob::tag`].
+    pub fn compute_batch(jobs: &mut [Zuc128MacJob<'_>]) {
+        compute_batch(jobs);
+    }
+
+    /// Compute the MAC of a message on multiple threads
+    ///
+    /// The result is identical to [`compute`](Self::compute).
+    ///
+    /// # Panics
+    /// + Panics if `bitlen` is greater than the bit length of `msg`.
+    #[cfg(feature = "rayon")]
+    #[must_use]
+    pub fn compute_parallel(ik: &[u8; 16], iv: &[u8; 16], msg: &[u8], bitlen: usize) -> u32 {
+        let zuc = Zuc128Keystream::new(ik, iv);
+        compute_parallel(zuc, msg, bitlen, MacProfile::Zuc128)
+    }
+
+    /// Compute the MACs of multiple messages on multiple threads
+    ///
+    /// Groups of jobs run on the rayon thread pool, each as in [`compute_batch`](Self::compute_batch).
+    /// The MAC of each job is stored in [`Zuc128MacJob::tag`] and does not depend on the grouping.
+    #[cfg(feature = "rayon")]
+    pub fn compute_batch_parallel(jobs: &mut [Zuc128MacJob<'_>]) {
+        compute_batch_parallel(jobs);
+    }
+
+    /// Compute the MAC of a message with a pregenerated keystream
+    ///
+    /// The keystream starts at the first word after initialization
+    /// and has [`keystream_len(bitlen)`](Self::keystream_len) words.
+    ///
+    /// # Panics
+    /// + Panics if the keystream length is not [`keystream_len(bitlen)`](Self::keystream_len).
+    /// + Panics if `bitlen` is greater than the bit length of `msg`.
+    #[must_use]
+    pub fn compute_with_keystream(keystream: &[u32], msg: &[u8], bitlen: usize) -> u32 {
+        compute_with_keystream(keystream, msg, bitlen, MacProfile::Zuc128)
+    }
+
+    /// Number of keystream words used by the MAC of a `bitlen`-bit message
+    #[must_use]
+    pub fn keystream_len(bitlen: usize) -> usize {
+        keystream_len::<u32>(bitlen, MacProfile::Zuc128)
+    }
+
     /// Create a new ZUC128 MAC generator
     #[must_use]
     pub fn new(ik: &[u8; 16], iv: &[u8; 16]) -> Self {
-        let mut zuc = Zuc128Keystream::new(ik, iv);
-        let key = u64::gen_key_pair(&mut zuc);
+        Self::from_keystream(Zuc128Keystream::new(ik, iv))
+    }
+
+    /// Create a ZUC128 MAC generator from an initialized keystream
+    pub(crate) fn from_keystream(zuc: Zuc128Keystream) -> Self {
+        Self(MacCore::new(zuc, MacProfile::Zuc128))
+    }
+
+    /// Forks the MAC generator after the message absorbed so far
+    #[must_use]
+    pub fn fork(&self) -> Self {
+        self.clone()
+    }
+
+    /// Exports the state of the MAC generator
+    ///
+    /// See [`MacState`] for the format and the security notes.
+    #[must_use]
+    pub fn export_state(&self) -> MacState {
+        MacState::export(algorithm::ZUC128_MAC, &self.0)
+    }
 
-        Self(MacCore {
-            zuc,
-            key,
-            tag: 0,
-            rem: default(),
-            cnt: 0,
-        })
+    /// Creates a ZUC128 MAC generator from an exported state
+    ///
+    /// # Errors
+    /// Returns an error if the state is exported from another algorithm or MAC size.
+    pub fn import_state(state: &MacState) -> Result<Self, StateError> {
+        state.import(algorithm::ZUC128_MAC).map(Self)
     }
 
     /// Update the MAC generator with the bytes of a message
@@ -50,20 +121,57 @@ impl Zuc128Mac {
     /// Finish the MAC generation and return the MAC
     #[must_use]
     pub fn finish(mut self, tail: &[u8], bitlen: usize) -> u32 {
-        let final_bitlen = self.0.finish(tail, bitlen);
-
-        let mut tag = self.0.tag;
-        let key = self.0.key;
+        self.0.finalize(tail, bitlen, MacProfile::Zuc128)
+    }
+}
 
-        tag ^= key.high();
+/// ZUC128 MAC job for [`Zuc128Mac::compute_batch`]
+#[derive(Debug, Clone)]
+pub struct Zuc128MacJob<'a> {
+    /// integrity key
+    pub ik: &'a [u8; 16],
+    /// initial vector
+    pub iv: &'a [u8; 16],
+    /// the input message
+    pub msg: &'a [u8],
+    /// bit length of the input message
+    pub bitlen: usize,
+    /// the output MAC
+    pub tag: Option<u32>,
+}
 
-        if final_bitlen == 0 {
-            tag ^= key.truncating_cast::<u32>();
-        } else {
-            tag ^= self.0.zuc.generate();
+impl<'a> Zuc128MacJob<'a> {
+    /// Create a new ZUC128 MAC job
+    #[must_use]
+    pub fn new(ik: &'a [u8; 16], iv: &'a [u8; 16], msg: &'a [u8], bitlen: usize) -> Self {
+        Self {
+            ik,
+            iv,
+            msg,
+            bitlen,
+            tag: None,
         }
+    }
+}
+
+impl<'a> MacJob<'a> for Zuc128MacJob<'a> {
+    type Keystream = Zuc128Keystream;
+    type Word = u32;
 
-        tag
+    fn load(&self) -> Zuc {
+        Zuc128Keystream::load(self.ik, self.iv)
+    }
+
+    fn start(&self, zuc: Zuc128Keystream) -> MacCore<Zuc128Keystream, u32> {
+        Zuc128Mac::from_keystream(zuc).0
+    }
+
+    fn message(&self) -> (&'a [u8], usize) {
+        (self.msg, self.bitlen)
+    }
+
+    fn complete(&mut self, core: MacCore<Zuc128Keystream, u32>, tail: &[u8], bitlen: usize) {
+        self.tag = Some(Zuc128Mac(core).finish(tail, bitlen));
     }
 }
 
@@ -90,10 +198,99 @@ impl digest::MacMarker for Zuc128Mac {}
 mod tests {
     use super::*;
 
+    use numeric_cast::TruncatingCast;
+
     #[test]
     fn test_digest() {
         fn require_digest_mac<T: digest::Mac>() {}
 
         require_digest_mac::<Zuc128Mac>();
     }
+
+    #[test]
+    fn batch() {
+        let ik = [0x3d; 16];
+        let ivs: [[u8; 16]; 7] = core::array::from_fn(|i| [i.truncating_cast::<u8>(); 16]);
+        let msg: [u8; 100] = core::array::from_fn(|i| (i * 7).truncating_cast::<u8>());
+        let bitlens = [0, 1, 31, 32, 33, 400, 799];
+
+        let mut jobs: [_; 7] =
+            core::array::from_fn(|i| Zuc128MacJob::new(&ik, &ivs[i], &msg, bitlens[i]));
+        Zuc128Mac::compute_batch(&mut jobs);
+
+        for job in &jobs {
+            let expected = Zuc128Mac::compute(job.ik, job.iv, job.msg, job.bitlen);
+            assert_eq!(job.tag, Some(expected));
+        }
+    }
+
+    #[test]
+    fn fork() {
+        let (ik, iv) = ([0x5c; 16], [0xa3; 16]);
+        let msgs: [[u8; 40]; 2] = core::array::from_fn(|i| {
+            core::array::from_fn(|j| {
+                if j < 13 {
+                    0x17
+                } else {
+                    (i * 40 + j).truncating_cast::<u8>()
+                }
+            })
+        });
+
+        let mut prefix = Zuc128Mac::new(&ik, &iv);
+        prefix.update(&msgs[0][..13]);
+
+        for msg in &msgs {
+            let mut mac = prefix.fork();
+            mac.update(&msg[13..30]);
+            let tag = mac.finish(&msg[30..], 75);
+            assert_eq!(tag, Zuc128Mac::compute(&ik, &iv, msg, 30 * 8 + 75));
+        }
+    }
+
+    #[test]
+    fn with_keystream() {
+        let (ik, iv) = ([0x6b; 16], [0x1d; 16]);
+        let msg: [u8; 100] = core::array::from_fn(|i| (i * 13).truncating_cast::<u8>());
+        let mut keystream = [0; 32];
+
+        for bitlen in [0, 1, 31, 32, 33, 64, 799, 800] {
+            let n = Zuc128Mac::keystream_len(bitlen);
+            let mut zuc = Zuc128Keystream::new(&ik, &iv);
+            keystream[..n].fill_with(|| zuc.generate());
+
+            let tag = Zuc128Mac::compute_with_keystream(&keystream[..n], &msg, bitlen);
+            assert_eq!(tag, Zuc128Mac::compute(&ik, &iv, &msg, bitlen));
+        }
+    }
+
+    #[should_panic(expected = "keystream length does not match the message")]
+    #[test]
+    fn with_short_keystream() {
+        let _ = Zuc128Mac::compute_with_keystream(&[0; 3], &[0; 8], 64);
+    }
+
+    #[test]
+    fn keystream_position() {
+        let msg = [0x5a; 1000];
+        let mut mac = Zuc128Mac::new(&[0; 16], &[0; 16]);
+        for (i, part) in msg.chunks(333).enumerate() {
+            mac.update(part);
+            // the key pair and one word per whole message word, nothing ahead
+            let words = (i * 333 + part.len()) / 4;
+            assert_eq!(mac.0.zuc.position().words(), 2 + words as u64);
+        }
+
+        // the tail takes exactly the words of the message end
+        let tag = mac.0.finalize(&[0xa5], 5, MacProfile::Zuc128);
+        let bitlen = msg.len() * 8 + 5;
+        assert_eq!(
+            mac.0.zuc.position().words(),
+            keystream_len::<u32>(bitlen, MacProfile::Zuc128) as u64
+        );
+
+        let mut full = [0x5a; 1001];
+        full[1000] = 0xa5;
+        assert_eq!(tag, Zuc128Mac::compute(&[0; 16], &[0; 16], &full, bitlen));
+    }
 }
@@ -1,11 +1,42 @@
 use super::Zuc128Keystream;
 
-use crate::internal::stream_cipher::xor_inplace;
+#[cfg(feature = "rayon")]
+use crate::internal::parallel::xor_batch_parallel;
+use crate::internal::seek::{impl_seekable, Checkpoints};
+use crate::internal::stream_cipher::{xor_batch, xor_inplace, XorJob};
+use crate::internal::zuc::Zuc;
 
 /// ZUC128 stream cipher
 /// ([GB/T 33133.1-2016](https://openstd.samr.gov.cn/bzgk/gb/newGbInfo?hcno=8C41A3AEECCA52B5C0011C8010CF0715))
 pub type Zuc128StreamCipher = cipher::StreamCipherCoreWrapper<Zuc128Keystream>;
 
+/// Seekable ZUC128 stream cipher
+/// ([GB/T 33133.1-2016](https://openstd.samr.gov.cn/bzgk/gb/newGbInfo?hcno=8C41A3AEECCA52B5C0011C8010CF0715))
+///
+/// It implements [`StreamCipherSeek`](cipher::StreamCipherSeek) with [`Zuc128SeekableKeystream`].
+pub type Zuc128SeekableStreamCipher = cipher::StreamCipherCoreWrapper<Zuc128SeekableKeystream>;
+
+/// Seekable ZUC128 keystream generator, the core of [`Zuc128SeekableStreamCipher`]
+///
+/// ZUC cannot jump ahead, so seeking moves the keystream word by word,
+/// from the nearest of the current state, the initial state and the latest checkpoints.
+/// The checkpoints are stored here only, not in [`Zuc128Keystream`].
+#[derive(Debug, Clone)]
+pub struct Zuc128SeekableKeystream {
+    /// keystream generator
+    zuc: Zuc128Keystream,
+    /// states to seek from
+    checkpoints: Checkpoints,
+}
+
+impl_seekable!(Zuc128SeekableKeystream, Zuc128Keystream);
+
+impl cipher::KeyIvInit for Zuc128SeekableKeystream {
+    fn new(key: &cipher::Key<Self>, iv: &cipher::Iv<Self>) -> Self {
+        Self::new(Zuc128Keystream::new(key.as_ref(), iv.as_ref()))
+    }
+}
+
 /// ZUC128 confidentiality algorithm
 /// ([GB/T 33133.2-2021](https://openstd.samr.gov.cn/bzgk/gb/newGbInfo?hcno=5D3CBA3ADEC7989344BD1E63006EF2B3))
 ///
@@ -20,3 +51,57 @@ pub fn zuc128_xor_inplace(ck: &[u8; 16], iv: &[u8; 16], data: &mut [u8], bitlen:
     let mut zuc = Zuc128Keystream::new(ck, iv);
     xor_inplace(&mut zuc, data, bitlen);
 }
+
+/// ZUC128 confidentiality algorithm over multiple bitstreams
+///
+/// The keystream generation of independent jobs is interleaved.
+pub fn zuc128_xor_inplace_batch(jobs: &mut [Zuc128XorJob<'_>]) {
+    xor_batch(jobs);
+}
+
+/// ZUC128 confidentiality algorithm over multiple bitstreams on multiple threads
+///
+/// Groups of jobs run on the rayon thread pool, each as in [`zuc128_xor_inplace_batch`].
+/// The output of each job does not depend on the grouping.
+#[cfg(feature = "rayon")]
+pub fn zuc128_xor_inplace_parallel(jobs: &mut [Zuc128XorJob<'_>]) {
+    xor_batch_parallel(jobs);
+}
+
+/// ZUC128 confidentiality job for [`zuc128_xor_inplace_batch`]
+#[derive(Debug)]
+pub struct Zuc128XorJob<'a> {
+    /// confidentiality key
+    pub ck: &'a [u8; 16],
+    /// initial vector
+    pub iv: &'a [u8; 16],
+    /// the bitstream
+    pub data: &'a mut [u8],
+    /// bit length of the bitstream
+    pub bitlen: usize,
+}
+
+impl<'a> Zuc128XorJob<'a> {
+    /// Create a new ZUC128 confidentiality job
+    #[must_use]
+    pub fn new(ck: &'a [u8; 16], iv: &'a [u8; 16], data: &'a mut [u8], bitlen: usize) -> Self {
+        Self {
+            ck,
+            iv,
+            data,
+            bitlen,
+        }
+    }
+}
+
+impl XorJob for Zuc128XorJob<'_> {
+    type Keystream = Zuc128Keystream;
+
+    fn load(&self) -> Zuc {
+        Zuc128Keystream::load(self.ck, self.iv)
+    }
+
+    fn data(&mut self) -> (&mut [u8], usize) {
+        (self.data, self.bitlen)
+    }
+}
@@ -0,0 +1,329 @@
+use super::{Zuc256Keystream, Zuc256Mac};
+
+use crate::internal::keystream::Keystream;
+use crate::internal::mac::MacTag;
+
+use core::fmt;
+use core::marker::PhantomData;
+
+use aead::consts::{U0, U16, U32};
+use aead::generic_array::typenum::Unsigned;
+use aead::{AeadCore, AeadInPlace, Error, Key, KeyInit, KeySizeUser, Nonce, Tag};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroize;
+
+/// domain of the key derivation iv
+const DOMAIN_KDF: u8 = 0x01;
+
+/// domain of the message iv
+const DOMAIN_MESSAGE: u8 = 0x00;
+
+/// Encrypt-then-MAC AEAD on ZUC256
+/// ([ZUC256-version1.1](http://www.is.cas.cn/ztzl2016/zouchongzhi/201801/W020180416526664982687.pdf))
+///
+/// The tag type `T` is `u32`, `u64` or `u128`. The key is 256 bits and the nonce is 128 bits.
+///
+/// ## Key separation
+/// The first 16 words of the [`Zuc256Keystream`] with the key `K` and the iv of the zero nonce
+/// in the key derivation domain are split into the encryption key `KE` (words 0 to 7)
+/// and the MAC key `KM` (words 8 to 15), as big endian bytes.
+/// `K` itself is used only for this derivation.
+///
+/// ## Nonce layout
+/// The 184-bit iv is laid out as 23 bytes:
+///
+/// | bytes    | value                                                  |
+/// | -------- | ------------------------------------------------------ |
+/// | 0 .. 16  | nonce                                                  |
+/// | 16       | domain: `0x00` for messages, `0x01` for key derivation |
+/// | 17       | byte size of the tag, 4, 8 or 16                       |
+/// | 18 .. 23 | zero                                                   |
+///
+/// A nonce must never be reused with the same key.
+///
+/// ## Encryption
+/// The ciphertext is the plaintext XOR-ed with the [`Zuc256Keystream`] of `KE` and the iv.
+/// The tag is the [`Zuc256Mac<T>`] of `KM` and the iv, in big endian bytes, over
+///
+/// ```text
+/// AD || zeros(pad16(AD)) || C || zeros(pad16(C)) || be64(len(AD)) || be64(len(C))
+/// ```
+///
+/// where `AD` is the associated data, `C` is the ciphertext, lengths are in bytes,
+/// and `pad16(x)` is the number of zero bytes that pads `x` to a multiple of 16 bytes.
+///
+/// The tag is verified in constant time before decryption,
+/// and the buffer is left untouched if the verification fails.
+///
+/// # Examples
+/// ```
+/// use zuc::aead::{AeadInPlace, KeyInit};
+/// use zuc::zuc256::Zuc256Aead128;
+///
+/// let aead = Zuc256Aead128::new(&[0x42; 32].into());
+/// let nonce = [0x07; 16].into();
+///
+/// let mut buffer = *b"hello, world";
+/// let tag = aead.encrypt_in_place_detached(&nonce, b"header", &mut buffer).unwrap();
+/// aead.decrypt_in_place_detached(&nonce, b"header", &mut buffer, &tag).unwrap();
+/// assert_eq!(&buffer, b"hello, world");
+/// ```
+#[derive(Clone)]
+pub struct Zuc256Aead<T: MacTag> {
+    /// encryption key
+    ke: [u8; 32],
+    /// MAC key
+    km: [u8; 32],
+    /// tag type
+    _tag: PhantomData<T>,
+}
+
+/// [`Zuc256Aead`] with 64 bits tag
+pub type Zuc256Aead64 = Zuc256Aead<u64>;
+
+/// [`Zuc256Aead`] with 128 bits tag
+pub type Zuc256Aead128 = Zuc256Aead<u128>;
+
+/// Constructs the ZUC256 iv of a nonce in a domain
+pub(super) fn layout_iv(nonce: &[u8], domain: u8, tag_size: u8) -> [u8; 23] {
+    let mut iv = [0; 23];
+    iv[..16].copy_from_slice(nonce);
+    iv[16] = domain;
+    iv[17] = tag_size;
+    iv
+}
+
+/// Derives the encryption key and the MAC key from the first 16 words of the keystream
+pub(super) fn derive_keys(key: &[u8; 32], iv: &[u8; 23]) -> ([u8; 32], [u8; 32]) {
+    let mut keys = [0; 64];
+    Zuc256Keystream::new(key, iv).fill_bytes(&mut keys);
+
+    let mut ke = [0; 32];
+    let mut km = [0; 32];
+    ke.copy_from_slice(&keys[..32]);
+    km.copy_from_slice(&keys[32..]);
+    keys.zeroize();
+
+    (ke, km)
+}
+
+/// Feeds the associated data and the message with their padding and lengths, and returns the MAC
+pub(super) fn authenticate<T: MacTag>(mut mac: Zuc256Mac<T>, ad: &[u8], msg: &[u8]) -> T {
+    let zeros = [0; 16];
+    let pad = |len: usize| &zeros[..(16 - len % 16) % 16];
+
+    let mut lens = [0; 16];
+    lens[..8].copy_from_slice(&(ad.len() as u64).to_be_bytes());
+    lens[8..].copy_from_slice(&(msg.len() as u64).to_be_bytes());
+
+    mac.update(ad);
+    mac.update(pad(ad.len()));
+    mac.update(msg);
+    mac.update(pad(msg.len()));
+    mac.finish(&lens, 128)
+}
+
+impl<T: MacTag> Zuc256Aead<T> {
+    /// Constructs the ZUC256 iv
+    fn iv(domain: u8, nonce: &[u8]) -> [u8; 23] {
+        layout_iv(nonce, domain, T::Size::U8)
+    }
+
+    /// Computes the tag of the associated data and the ciphertext
+    fn tag(&self, iv: &[u8; 23], ad: &[u8], ciphertext: &[u8]) -> Tag<Self> {
+        let mac = Zuc256Mac::<T>::new(&self.km, iv);
+        authenticate(mac, ad, ciphertext).to_bytes()
+    }
+}
+
+impl<T: MacTag> KeySizeUser for Zuc256Aead<T> {
+    type KeySize = U32;
+}
+
+impl<T: MacTag> KeyInit for Zuc256Aead<T> {
+    fn new(key: &Key<Self>) -> Self {
+        let iv = Self::iv(DOMAIN_KDF, &[0; 16]);
+        let (ke, km) = derive_keys(&(*key).into(), &iv);
+        Self {
+            ke,
+            km,
+            _tag: PhantomData,
+        }
+    }
+}
+
+impl<T: MacTag> AeadCore for Zuc256Aead<T> {
+    type NonceSize = U16;
+    type TagSize = T::Size;
+    type CiphertextOverhead = U0;
+}
+
+impl<T: MacTag> AeadInPlace for Zuc256Aead<T> {
+    fn encrypt_in_place_detached(
+        &self,
+        nonce: &Nonce<Self>,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+    ) -> aead::Result<Tag<Self>> {
+        let iv = Self::iv(DOMAIN_MESSAGE, nonce);
+        Zuc256Keystream::new(&self.ke, &iv).xor_bits(buffer, buffer.len() * 8);
+        Ok(self.tag(&iv, associated_data, buffer))
+    }
+
+    fn decrypt_in_place_detached(
+        &self,
+        nonce: &Nonce<Self>,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+        tag: &Tag<Self>,
+    ) -> aead::Result<()> {
+        let iv = Self::iv(DOMAIN_MESSAGE, nonce);
+        let expected = self.tag(&iv, associated_data, buffer);
+        if !bool::from(expected.ct_eq(tag)) {
+            return Err(Error);
+        }
+        Zuc256Keystream::new(&self.ke, &iv).xor_bits(buffer, buffer.len() * 8);
+        Ok(())
+    }
+}
+
+impl<T: MacTag> Drop for Zuc256Aead<T> {
+    fn drop(&mut self) {
+        self.ke.zeroize();
+        self.km.zeroize();
+    }
+}
+
+impl<T: MacTag> fmt::Debug for Zuc256Aead<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Zuc256Aead").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use aead::generic_array::GenericArray;
+    use numeric_cast::TruncatingCast;
+
+    fn message() -> [u8; 100] {
+        core::array::from_fn(|i| (i * 13 + 5).truncating_cast::<u8>())
+    }
+
+    /// the documented construction, without the `aead` traits
+    fn reference<T: MacTag>(
+        key: &[u8; 32],
+        nonce: &[u8; 16],
+        ad: &[u8],
+        msg: &mut [u8],
+    ) -> GenericArray<u8, T::Size> {
+        let mut kdf_iv = [0; 23];
+        kdf_iv[16] = 0x01;
+        kdf_iv[17] = T::Size::U8;
+        let mut keys = [0; 64];
+        Zuc256Keystream::new(key, &kdf_iv).fill_bytes(&mut keys);
+        let ke: [u8; 32] = keys[..32].try_into().unwrap();
+        let km: [u8; 32] = keys[32..].try_into().unwrap();
+
+        let mut iv = [0; 23];
+        iv[..16].copy_from_slice(nonce);
+        iv[17] = T::Size::U8;
+
+        let mut ks = [0; 128];
+        Zuc256Keystream::new(&ke, &iv).fill_bytes(&mut ks);
+        for (m, k) in msg.iter_mut().zip(ks) {
+            *m ^= k;
+        }
+
+        let mut input = [0; 256];
+        let mut len = 0;
+        for part in [ad, msg] {
+            input[len..len + part.len()].copy_from_slice(part);
+            len += part.len().div_ceil(16) * 16;
+        }
+        input[len..len + 8].copy_from_slice(&(ad.len() as u64).to_be_bytes());
+        input[len + 8..len + 16].copy_from_slice(&(msg.len() as u64).to_be_bytes());
+        len += 16;
+
+        Zuc256Mac::<T>::compute(&km, &iv, &input[..len], len * 8).to_bytes()
+    }
+
+    #[test]
+    fn construction() {
+        fn check<T: MacTag>() {
+            let key = [0x9c; 32];
+            let nonce = [0x3e; 16];
+            let aead = Zuc256Aead::<T>::new(&key.into());
+            let msg = message();
+
+            for (ad_len, msg_len) in [(0, 0), (0, 1), (13, 0), (16, 16), (17, 31), (40, 100)] {
+                let ad = &msg[..ad_len];
+                let mut buffer = msg;
+                let tag = aead
+                    .encrypt_in_place_detached(&nonce.into(), ad, &mut buffer[..msg_len])
+                    .unwrap();
+
+                let mut expected = msg;
+                let expected_tag = reference::<T>(&key, &nonce, ad, &mut expected[..msg_len]);
+                assert_eq!(buffer, expected);
+                assert_eq!(tag, expected_tag);
+            }
+        }
+
+        check::<u32>();
+        check::<u64>();
+        check::<u128>();
+    }
+
+    #[test]
+    fn tamper() {
+        let aead = Zuc256Aead128::new(&[0x51; 32].into());
+        let nonce = [0x0a; 16].into();
+        let msg = message();
+
+        let mut ciphertext = msg;
+        let tag = aead
+            .encrypt_in_place_detached(&nonce, b"ad", &mut ciphertext)
+            .unwrap();
+
+        let decrypt = |nonce: &[u8; 16], ad: &[u8], ct: &[u8; 100], tag: &Tag<Zuc256Aead128>| {
+            let mut buffer = *ct;
+            let result = aead.decrypt_in_place_detached(&(*nonce).into(), ad, &mut buffer, tag);
+            if result.is_err() {
+                assert_eq!(buffer, *ct);
+            }
+            result.map(|()| buffer)
+        };
+
+        assert_eq!(decrypt(&[0x0a; 16], b"ad", &ciphertext, &tag), Ok(msg));
+        assert!(decrypt(&[0x0b; 16], b"ad", &ciphertext, &tag).is_err());
+        assert!(decrypt(&[0x0a; 16], b"ae", &ciphertext, &tag).is_err());
+        assert!(decrypt(&[0x0a; 16], b"", &ciphertext, &tag).is_err());
+
+        let mut forged = ciphertext;
+        forged[99] ^= 0x01;
+        assert!(decrypt(&[0x0a; 16], b"ad", &forged, &tag).is_err());
+
+        let mut forged = tag;
+        forged[0] ^= 0x80;
+        assert!(decrypt(&[0x0a; 16], b"ad", &ciphertext, &forged).is_err());
+    }
+
+    #[test]
+    fn tag_size() {
+        let key = [0x77; 32].into();
+        let nonce = [0; 16].into();
+        let mut a = message();
+        let mut b = message();
+        let t64 = Zuc256Aead64::new(&key)
+            .encrypt_in_place_detached(&nonce, &[], &mut a)
+            .unwrap();
+        let t128 = Zuc256Aead128::new(&key)
+            .encrypt_in_place_detached(&nonce, &[], &mut b)
+            .unwrap();
+        assert_eq!(t64.len(), 8);
+        assert_eq!(t128.len(), 16);
+        assert_ne!(a, b);
+    }
+}
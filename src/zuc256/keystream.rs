@@ -1,5 +1,7 @@
-use crate::internal::keystream::Keystream;
+use crate::internal::keystream::{Keystream, Sealed, ZucKeystream};
+use crate::internal::seek::Position;
 use crate::internal::zuc::Zuc;
+use crate::state::{algorithm, KeystreamState, StateError};
 
 /// d constants
 static D: [u8; 16] = [
@@ -32,6 +34,8 @@ fn concat_bits(a: u8, b: u8, c: u8, d: u8) -> u32 {
 pub struct Zuc256Keystream {
     /// zuc core
     core: Zuc,
+    /// number of words generated since initialization
+    pos: u64,
 }
 
 impl Zuc256Keystream {
@@ -41,9 +45,19 @@ impl Zuc256Keystream {
         Zuc256Keystream::new_with_d(k, iv, &D)
     }
 
-    /// Creates a [`Zuc256Core`] with specific d constants
-    pub(crate) fn new_with_d(k: &[u8; 32], iv: &[u8; 23], d: &[u8; 16]) -> Self {
-        let mut zuc = Zuc::zeroed();
+    /// Creates a ZUC256 keystream generator with specific d constants
+    ///
+    /// The ZUC256 MACs use d constants depending on the tag size,
+    /// other profiles of ZUC256 may define their own.
+    #[must_use]
+    pub fn new_with_d(k: &[u8; 32], iv: &[u8; 23], d: &[u8; 16]) -> Self {
+        let mut zuc = Self::load_with_d(k, iv, d);
+        zuc.init();
+        Self::from_core(zuc)
+    }
+
+    /// Loads the key, iv and d constants into a ZUC core without initialization
+    pub(crate) fn load_with_d(k: &[u8; 32], iv: &[u8; 23], d: &[u8; 16]) -> Zuc {
         // extend from 184bit iv[0..=22] (u8*23) to iv[0..=24](8bit*17 + 6bit *8)
         let iv17: u8 = iv[17] >> 2;
         let iv18: u8 = ((iv[17] & 0x3) << 4) | (iv[18] >> 4);
@@ -54,42 +68,114 @@ impl Zuc256Keystream {
         let iv23: u8 = ((iv[21] & 0xf) << 2) | (iv[22] >> 6);
         let iv24: u8 = iv[22] & 0x3f;
 
-        zuc.s[0] = concat_bits(k[0], d[0], k[21], k[16]);
-        zuc.s[1] = concat_bits(k[1], d[1], k[22], k[17]);
-        zuc.s[2] = concat_bits(k[2], d[2], k[23], k[18]);
-        zuc.s[3] = concat_bits(k[3], d[3], k[24], k[19]);
-        zuc.s[4] = concat_bits(k[4], d[4], k[25], k[20]);
-        zuc.s[5] = concat_bits(iv[0], d[5] | iv17, k[5], k[26]);
-        zuc.s[6] = concat_bits(iv[1], d[6] | iv18, k[6], k[27]);
-        zuc.s[7] = concat_bits(iv[10], d[7] | iv19, k[7], iv[2]);
-        zuc.s[8] = concat_bits(k[8], d[8] | iv20, iv[3], iv[11]);
-        zuc.s[9] = concat_bits(k[9], d[9] | iv21, iv[12], iv[4]);
-        zuc.s[10] = concat_bits(iv[5], d[10] | iv22, k[10], k[28]);
-        zuc.s[11] = concat_bits(k[11], d[11] | iv23, iv[6], iv[13]);
-        zuc.s[12] = concat_bits(k[12], d[12] | iv24, iv[7], iv[14]);
-        zuc.s[13] = concat_bits(k[13], d[13], iv[15], iv[8]);
-        zuc.s[14] = concat_bits(k[14], d[14] | (k[31] >> 4), iv[16], iv[9]);
-        zuc.s[15] = concat_bits(k[15], d[15] | (k[31] & 0b_1111), k[30], k[29]);
-        zuc.init();
-        Self { core: zuc }
+        let s = [
+            concat_bits(k[0], d[0], k[21], k[16]),
+            concat_bits(k[1], d[1], k[22], k[17]),
+            concat_bits(k[2], d[2], k[23], k[18]),
+            concat_bits(k[3], d[3], k[24], k[19]),
+            concat_bits(k[4], d[4], k[25], k[20]),
+            concat_bits(iv[0], d[5] | iv17, k[5], k[26]),
+            concat_bits(iv[1], d[6] | iv18, k[6], k[27]),
+            concat_bits(iv[10], d[7] | iv19, k[7], iv[2]),
+            concat_bits(k[8], d[8] | iv20, iv[3], iv[11]),
+            concat_bits(k[9], d[9] | iv21, iv[12], iv[4]),
+            concat_bits(iv[5], d[10] | iv22, k[10], k[28]),
+            concat_bits(k[11], d[11] | iv23, iv[6], iv[13]),
+            concat_bits(k[12], d[12] | iv24, iv[7], iv[14]),
+            concat_bits(k[13], d[13], iv[15], iv[8]),
+            concat_bits(k[14], d[14] | (k[31] >> 4), iv[16], iv[9]),
+            concat_bits(k[15], d[15] | (k[31] & 0b_1111), k[30], k[29]),
+        ];
+        Zuc { s, r1: 0, r2: 0 }
     }
 
     ///  Generates the next 32-bit word in ZUC256 keystream
     #[must_use]
     pub fn generate(&mut self) -> u32 {
+        self.pos += 1;
         self.core.generate()
     }
+
+    /// Returns the number of words generated since initialization
+    #[must_use]
+    pub fn position(&self) -> Position {
+        Position::from_words(self.pos)
+    }
+
+    /// Steps back by one word and returns the word generated last
+    ///
+    /// The next call to [`generate`](Self::generate) returns the same word again.
+    ///
+    /// # Panics
+    /// + Panics if no word has been generated.
+    pub fn step_back(&mut self) -> u32 {
+        assert!(self.pos > 0, "cannot step back before the initial state");
+        self.pos -= 1;
+        self.core.step_back()
+    }
+
+    /// Forks the keystream generator at its current position
+    #[must_use]
+    pub fn fork(&self) -> Self {
+        self.clone()
+    }
+
+    /// Exports the state of the keystream generator
+    ///
+    /// See [`KeystreamState`] for the format and the security notes.
+    #[must_use]
+    pub fn export_state(&self) -> KeystreamState {
+        KeystreamState::export(algorithm::ZUC256, self)
+    }
+
+    /// Creates a ZUC256 keystream generator from an exported state
+    ///
+    /// # Errors
+    /// Returns an error if the state is exported from another algorithm.
+    pub fn import_state(state: &KeystreamState) -> Result<Self, StateError> {
+        state.import(algorithm::ZUC256)
+    }
 }
 
-impl Keystream for Zuc256Keystream {
-    type Word = u32;
+impl Sealed for Zuc256Keystream {}
 
+impl Keystream for Zuc256Keystream {
     #[inline]
-    fn next_key(&mut self) -> Self::Word {
+    fn next_key(&mut self) -> u32 {
         self.generate()
     }
 }
 
+impl ZucKeystream for Zuc256Keystream {
+    fn from_core(core: Zuc) -> Self {
+        Self { core, pos: 0 }
+    }
+
+    fn resume(core: Zuc, pos: u64) -> Self {
+        Self { core, pos }
+    }
+
+    fn core(&self) -> &Zuc {
+        &self.core
+    }
+
+    fn core_mut(&mut self) -> &mut Zuc {
+        &mut self.core
+    }
+
+    fn parts_mut(&mut self) -> (&mut Zuc, &mut u64) {
+        (&mut self.core, &mut self.pos)
+    }
+
+    fn position(&self) -> u64 {
+        self.pos
+    }
+
+    fn step_back(&mut self) -> u32 {
+        Self::step_back(self)
+    }
+}
+
 impl Iterator for Zuc256Keystream {
     type Item = u32;
 
@@ -236,4 +322,26 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn position() {
+        use crate::zuc256::Zuc256SeekableStreamCipher;
+        use cipher::{KeyIvInit, StreamCipher, StreamCipherSeek};
+
+        let Example { k, iv, .. } = &EXAMPLE1;
+        let mut zuc = Zuc256Keystream::new(k, iv);
+        assert_eq!(zuc.position(), Position::default());
+        for _ in 0..3 {
+            let _ = zuc.generate();
+        }
+        assert_eq!(zuc.position().words(), 3);
+        assert_eq!(zuc.position().bytes(), 12);
+        assert_eq!(zuc.position().bits(), 96);
+
+        let mut cipher = Zuc256SeekableStreamCipher::new(k.into(), iv.into());
+        let mut data = [0; 10];
+        cipher.apply_keystream(&mut data);
+        assert_eq!(cipher.current_pos::<u64>(), 10);
+        assert_eq!(cipher.get_core().position().bytes(), 12);
+    }
 }
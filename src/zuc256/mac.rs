@@ -1,18 +1,18 @@
 use super::Zuc256Keystream;
 
-use crate::internal::mac::{MacCore, MacKeyPair, MacWord};
+use crate::internal::mac::{
+    compute_batch, compute_with_keystream, keystream_len, MacCore, MacJob, MacProfile, MacTag,
+};
+#[cfg(feature = "rayon")]
+use crate::internal::parallel::{compute_batch_parallel, compute_parallel};
+use crate::internal::zuc::Zuc;
+use crate::state::{algorithm, MacState, StateError};
 
 use core::mem::size_of;
 
-use stdx::default::default;
-
-pub trait MacTag: MacWord {}
-impl MacTag for u32 {}
-impl MacTag for u64 {}
-impl MacTag for u128 {}
-
 /// ZUC256 MAC generator
 /// ([ZUC256-version1.1](http://www.is.cas.cn/ztzl2016/zouchongzhi/201801/W020180416526664982687.pdf))
+#[derive(Clone)]
 pub struct Zuc256Mac<T: MacTag>(MacCore<Zuc256Keystream, T>);
 
 impl<T: MacTag> Zuc256Mac<T> {
@@ -34,27 +34,99 @@ impl<T: MacTag> Zuc256Mac<T> {
         Self::new(ik, iv).finish(msg, bitlen)
     }
 
+    /// Compute the MACs of multiple messages
+    ///
+    /// The keystream generation of independent jobs is interleaved.
+    /// The MAC of each job is stored in [`Zuc256MacJob::tag`].
+    pub fn compute_batch(jobs: &mut [Zuc256MacJob<'_, T>]) {
+        compute_batch(jobs);
+    }
+
+    /// Compute the MAC of a message on multiple threads
+    ///
+    /// The result is identical to [`compute`](Self::compute).
+    ///
+    /// # Panics
+    /// + Panics if `bitlen` is greater than the bit length of `msg`.
+    #[cfg(feature = "rayon")]
+    #[must_use]
+    pub fn compute_parallel(ik: &[u8; 32], iv: &[u8; 23], msg: &[u8], bitlen: usize) -> T {
+        let zuc = Zuc256Keystream::new_with_d(ik, iv, Self::d());
+        compute_parallel(zuc, msg, bitlen, MacProfile::Zuc256)
+    }
+
+    /// Compute the MACs of multiple messages on multiple threads
+    ///
+    /// Groups of jobs run on the rayon thread pool, each as in [`compute_batch`](Self::compute_batch).
+    /// The MAC of each job is stored in [`Zuc256MacJob::tag`] and does not depend on the grouping.
+    #[cfg(feature = "rayon")]
+    pub fn compute_batch_parallel(jobs: &mut [Zuc256MacJob<'_, T>]) {
+        compute_batch_parallel(jobs);
+    }
+
+    /// Compute the MAC of a message with a pregenerated keystream
+    ///
+    /// The keystream starts at the first word after initialization with the d constants of `T`,
+    /// and has [`keystream_len(bitlen)`](Self::keystream_len) words.
+    ///
+    /// # Panics
+    /// + Panics if the keystream length is not [`keystream_len(bitlen)`](Self::keystream_len).
+    /// + Panics if `bitlen` is greater than the bit length of `msg`.
+    #[must_use]
+    pub fn compute_with_keystream(keystream: &[u32], msg: &[u8], bitlen: usize) -> T {
+        compute_with_keystream(keystream, msg, bitlen, MacProfile::Zuc256)
+    }
+
+    /// Number of keystream words used by the MAC of a `bitlen`-bit message
+    #[must_use]
+    pub fn keystream_len(bitlen: usize) -> usize {
+        keystream_len::<T>(bitlen, MacProfile::Zuc256)
+    }
+
     /// Create a new ZUC256 MAC generator
     #[must_use]
     pub fn new(ik: &[u8; 32], iv: &[u8; 23]) -> Self {
-        let d = match size_of::<T>() {
+        Self::from_keystream(Zuc256Keystream::new_with_d(ik, iv, Self::d()))
+    }
+
+    /// Returns the d constants of the keystream for the MAC size
+    ///
+    /// See [`Zuc256Keystream::new_with_d`].
+    #[must_use]
+    pub fn d() -> &'static [u8; 16] {
+        match size_of::<T>() {
             4 => &D_32,
             8 => &D_64,
             16 => &D_128,
             _ => unreachable!(),
-        };
+        }
+    }
+
+    /// Create a ZUC256 MAC generator from an initialized keystream
+    fn from_keystream(zuc: Zuc256Keystream) -> Self {
+        Self(MacCore::new(zuc, MacProfile::Zuc256))
+    }
+
+    /// Forks the MAC generator after the message absorbed so far
+    #[must_use]
+    pub fn fork(&self) -> Self {
+        self.clone()
+    }
 
-        let mut zuc = Zuc256Keystream::new_with_d(ik, iv, d);
-        let tag: T = T::gen_word(&mut zuc);
-        let key: T::KeyPair = T::KeyPair::gen_key_pair(&mut zuc);
+    /// Exports the state of the MAC generator
+    ///
+    /// See [`MacState`] for the format and the security notes.
+    #[must_use]
+    pub fn export_state(&self) -> MacState {
+        MacState::export(algorithm::ZUC256_MAC, &self.0)
+    }
 
-        Self(MacCore {
-            zuc,
-            key,
-            tag,
-            rem: default(),
-            cnt: 0,
-        })
+    /// Creates a ZUC256 MAC generator from an exported state
+    ///
+    /// # Errors
+    /// Returns an error if the state is exported from another algorithm or MAC size.
+    pub fn import_state(state: &MacState) -> Result<Self, StateError> {
+        state.import(algorithm::ZUC256_MAC).map(Self)
     }
 
     /// Update the MAC generator with the bytes of a message
@@ -64,9 +136,57 @@ impl<T: MacTag> Zuc256Mac<T> {
 
     /// Finish the MAC generation and return the MAC
     pub fn finish(mut self, tail: &[u8], bitlen: usize) -> T {
-        let _ = self.0.finish(tail, bitlen);
-        self.0.tag ^= self.0.key.high();
-        self.0.tag
+        self.0.finalize(tail, bitlen, MacProfile::Zuc256)
+    }
+}
+
+/// ZUC256 MAC job for [`Zuc256Mac::compute_batch`]
+#[derive(Debug, Clone)]
+pub struct Zuc256MacJob<'a, T: MacTag> {
+    /// integrity key
+    pub ik: &'a [u8; 32],
+    /// initial vector
+    pub iv: &'a [u8; 23],
+    /// the input message
+    pub msg: &'a [u8],
+    /// bit length of the input message
+    pub bitlen: usize,
+    /// the output MAC
+    pub tag: Option<T>,
+}
+
+impl<'a, T: MacTag> Zuc256MacJob<'a, T> {
+    /// Create a new ZUC256 MAC job
+    #[must_use]
+    pub fn new(ik: &'a [u8; 32], iv: &'a [u8; 23], msg: &'a [u8], bitlen: usize) -> Self {
+        Self {
+            ik,
+            iv,
+            msg,
+            bitlen,
+            tag: None,
+        }
+    }
+}
+
+impl<'a, T: MacTag> MacJob<'a> for Zuc256MacJob<'a, T> {
+    type Keystream = Zuc256Keystream;
+    type Word = T;
+
+    fn load(&self) -> Zuc {
+        Zuc256Keystream::load_with_d(self.ik, self.iv, Zuc256Mac::<T>::d())
+    }
+
+    fn start(&self, zuc: Zuc256Keystream) -> MacCore<Zuc256Keystream, T> {
+        Zuc256Mac::from_keystream(zuc).0
+    }
+
+    fn message(&self) -> (&'a [u8], usize) {
+        (self.msg, self.bitlen)
+    }
+
+    fn complete(&mut self, core: MacCore<Zuc256Keystream, T>, tail: &[u8], bitlen: usize) {
+        self.tag = Some(Zuc256Mac(core).finish(tail, bitlen));
     }
 }
 
@@ -95,13 +215,13 @@ impl<T: MacTag> digest::Update for Zuc256Mac<T> {
 }
 
 impl<T: MacTag> digest::OutputSizeUser for Zuc256Mac<T> {
-    type OutputSize = <T as MacWord>::ByteSize;
+    type OutputSize = T::Size;
 }
 
 impl<T: MacTag> digest::FixedOutput for Zuc256Mac<T> {
     fn finalize_into(self, out: &mut digest::Output<Self>) {
         let tag = self.finish(&[], 0);
-        *out = tag.to_be_array();
+        *out = tag.to_bytes();
     }
 }
 
@@ -248,4 +368,103 @@ mod tests {
         require_digest_mac::<Zuc256Mac<u64>>();
         require_digest_mac::<Zuc256Mac<u128>>();
     }
+
+    #[test]
+    fn batch() {
+        fn check<T: MacTag>(expected: impl Fn(&Example) -> T) {
+            let mut jobs: [_; 4] = core::array::from_fn(|i| {
+                let x = ALL_EXAMPLES[i];
+                Zuc256MacJob::new(&x.k, &x.iv, x.m, x.length as usize)
+            });
+            Zuc256Mac::<T>::compute_batch(&mut jobs);
+            for (job, x) in jobs.iter().zip(ALL_EXAMPLES) {
+                assert_eq!(job.tag, Some(expected(x)));
+            }
+
+            let x = &EXAMPLE_MAC_2;
+            let mut jobs: [_; 6] = core::array::from_fn(|i| {
+                let bitlen = x.length as usize - i * 333;
+                Zuc256MacJob::<T>::new(&x.k, &x.iv, x.m, bitlen)
+            });
+            Zuc256Mac::<T>::compute_batch(&mut jobs);
+            for job in &jobs {
+                let expected = Zuc256Mac::<T>::compute(&x.k, &x.iv, x.m, job.bitlen);
+                assert_eq!(job.tag, Some(expected));
+            }
+        }
+
+        check(|x| x.expected_32);
+        check(|x| x.expected_64);
+        check(|x| x.expected_128);
+    }
+
+    #[test]
+    fn fork() {
+        fn check<T: MacTag>(expected: impl Fn(&Example) -> T) {
+            for x in ALL_EXAMPLES {
+                let bitlen = x.length as usize;
+                let split = x.m.len() / 3;
+
+                let mut prefix = Zuc256Mac::<T>::new(&x.k, &x.iv);
+                prefix.update(&x.m[..split]);
+
+                let mut mac = prefix.fork();
+                mac.update(&x.m[split..split * 2]);
+                assert_eq!(
+                    mac.finish(&x.m[split * 2..], bitlen - split * 16),
+                    expected(x)
+                );
+
+                let tag = prefix.fork().finish(&x.m[split..], 3);
+                let expected = Zuc256Mac::<T>::compute(&x.k, &x.iv, x.m, split * 8 + 3);
+                assert_eq!(tag, expected);
+            }
+        }
+
+        check(|x| x.expected_32);
+        check(|x| x.expected_64);
+        check(|x| x.expected_128);
+    }
+
+    #[test]
+    fn generic() {
+        use crate::mac::MacBuilder;
+
+        fn check<T: MacTag>(d: &[u8; 16]) {
+            let x = &EXAMPLE_MAC_2;
+            for bitlen in [0, 1, 64, 100, x.length as usize] {
+                let mut zuc = Zuc256Keystream::new_with_d(&x.k, &x.iv, d);
+                let mac = MacBuilder::new(&mut zuc).build::<T>();
+                let expected = Zuc256Mac::<T>::compute(&x.k, &x.iv, x.m, bitlen);
+                let tag = mac.finish(x.m, bitlen);
+                assert_eq!(tag, expected);
+                assert_eq!(T::from_bytes(&tag.to_bytes()), tag);
+            }
+        }
+
+        check::<u32>(&D_32);
+        check::<u64>(&D_64);
+        check::<u128>(&D_128);
+    }
+
+    #[test]
+    fn with_keystream() {
+        fn check<T: MacTag>(x: &Example) {
+            let mut keystream = [0; 256];
+            for bitlen in [0, 1, 32, 200, x.length as usize] {
+                let n = Zuc256Mac::<T>::keystream_len(bitlen);
+                let mut zuc = Zuc256Keystream::new_with_d(&x.k, &x.iv, Zuc256Mac::<T>::d());
+                keystream[..n].fill_with(|| zuc.generate());
+
+                let tag = Zuc256Mac::<T>::compute_with_keystream(&keystream[..n], x.m, bitlen);
+                assert_eq!(tag, Zuc256Mac::<T>::compute(&x.k, &x.iv, x.m, bitlen));
+            }
+        }
+
+        for x in ALL_EXAMPLES {
+            check::<u32>(x);
+            check::<u64>(x);
+            check::<u128>(x);
+        }
+    }
 }
@@ -0,0 +1,340 @@
+use super::aead::{authenticate, derive_keys, layout_iv};
+use super::{Zuc256Keystream, Zuc256Mac};
+
+use crate::internal::keystream::Keystream;
+
+use core::fmt;
+
+use aead::consts::{U0, U16, U32};
+use aead::{AeadCore, AeadInPlace, Error, Key, KeyInit, KeySizeUser, Nonce, Tag};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroize;
+
+/// domain of the key derivation iv
+const DOMAIN_KDF: u8 = 0x02;
+
+/// domain of the synthetic iv MAC
+const DOMAIN_MAC: u8 = 0x03;
+
+/// domain of the encryption iv
+const DOMAIN_CIPHER: u8 = 0x04;
+
+/// domain of the PRF key derivation iv
+const DOMAIN_KDF_PRF: u8 = 0x05;
+
+/// domain of the PRF iv
+const DOMAIN_PRF: u8 = 0x06;
+
+/// byte size of the synthetic iv
+const SIV_SIZE: u8 = 16;
+
+/// Deterministic authenticated encryption on ZUC256 with a synthetic iv
+/// ([ZUC256-version1.1](http://www.is.cas.cn/ztzl2016/zouchongzhi/201801/W020180416526664982687.pdf))
+///
+/// The key is 256 bits and the nonce is 128 bits. The tag is the 128-bit synthetic iv.
+/// The ivs are laid out as in [`Zuc256Aead`](super::Zuc256Aead), with the domains below
+/// and the byte size 16.
+///
+/// + The encryption key `KE` and the MAC key `KM` are derived from the key `K`
+///   as in [`Zuc256Aead`](super::Zuc256Aead), with the zero nonce in the domain `0x02`.
+///   The PRF key `KP` is the first half of the same derivation in the domain `0x05`.
+/// + `M` is the [`Zuc256Mac<u128>`] of `KM` and the nonce in the domain `0x03`,
+///   in big endian bytes, over the associated data and the plaintext,
+///   encoded as in [`Zuc256Aead`](super::Zuc256Aead).
+/// + The synthetic iv `S` is the first 128 bits of the [`Zuc256Keystream`] of `KP` and `M` in the domain `0x06`.
+///   The MAC is affine in the message bits for a fixed nonce, so it is not used as the iv directly.
+/// + The ciphertext is the plaintext XOR-ed with the [`Zuc256Keystream`] of `KE` and `S` in the domain `0x04`.
+///
+/// Decryption recomputes the synthetic iv from the decrypted plaintext and compares it in constant time.
+/// If the verification fails, the buffer is restored to the ciphertext.
+///
+/// Encrypting the same plaintext with the same associated data and nonce gives the same ciphertext,
+/// so a repeated nonce reveals whether the messages are equal.
+/// Without a nonce, as in [`wrap_key`](Self::wrap_key), it is deterministic authenticated encryption.
+///
+/// # Examples
+/// ```
+/// use zuc::aead::KeyInit;
+/// use zuc::zuc256::Zuc256Siv;
+///
+/// let kek = Zuc256Siv::new(&[0x42; 32].into());
+/// let key = [0x5a; 16];
+///
+/// let mut wrapped = [0; 32];
+/// kek.wrap_key(b"subscriber 1", &key, &mut wrapped).unwrap();
+///
+/// let mut unwrapped = [0; 16];
+/// kek.unwrap_key(b"subscriber 1", &wrapped, &mut unwrapped).unwrap();
+/// assert_eq!(unwrapped, key);
+/// ```
+#[derive(Clone)]
+pub struct Zuc256Siv {
+    /// encryption key
+    ke: [u8; 32],
+    /// MAC key
+    km: [u8; 32],
+    /// PRF key of the synthetic iv
+    kp: [u8; 32],
+}
+
+impl Zuc256Siv {
+    /// Byte size of the synthetic iv prepended by [`wrap_key`](Self::wrap_key)
+    pub const OVERHEAD: usize = SIV_SIZE as usize;
+
+    /// Computes the synthetic iv of the associated data and the plaintext
+    fn siv(&self, nonce: &[u8], ad: &[u8], plaintext: &[u8]) -> Tag<Self> {
+        let iv = layout_iv(nonce, DOMAIN_MAC, SIV_SIZE);
+        let mac = Zuc256Mac::<u128>::new(&self.km, &iv);
+        let m = authenticate(mac, ad, plaintext).to_be_bytes();
+
+        let iv = layout_iv(&m, DOMAIN_PRF, SIV_SIZE);
+        let mut siv = Tag::<Self>::default();
+        Zuc256Keystream::new(&self.kp, &iv).fill_bytes(&mut siv);
+        siv
+    }
+
+    /// XORs the buffer with the keystream of the synthetic iv
+    fn apply_keystream(&self, siv: &Tag<Self>, buffer: &mut [u8]) {
+        let iv = layout_iv(siv, DOMAIN_CIPHER, SIV_SIZE);
+        Zuc256Keystream::new(&self.ke, &iv).xor_bits(buffer, buffer.len() * 8);
+    }
+
+    /// Wraps a key with the associated data
+    ///
+    /// `wrapped` receives the synthetic iv followed by the encrypted key,
+    /// and its length must be the key length plus [`OVERHEAD`](Self::OVERHEAD).
+    ///
+    /// # Errors
+    /// Returns an error if the length of `wrapped` is wrong.
+    pub fn wrap_key(&self, ad: &[u8], key: &[u8], wrapped: &mut [u8]) -> aead::Result<()> {
+        if wrapped.len() != key.len() + Self::OVERHEAD {
+            return Err(Error);
+        }
+        let (siv, ciphertext) = wrapped.split_at_mut(Self::OVERHEAD);
+        ciphertext.copy_from_slice(key);
+        let tag = self.encrypt_in_place_detached(&Nonce::<Self>::default(), ad, ciphertext)?;
+        siv.copy_from_slice(&tag);
+        Ok(())
+    }
+
+    /// Unwraps a key wrapped by [`wrap_key`](Self::wrap_key) with the same associated data
+    ///
+    /// The length of `key` must be the length of `wrapped` minus [`OVERHEAD`](Self::OVERHEAD).
+    ///
+    /// # Errors
+    /// Returns an error if the lengths are wrong or the verification fails.
+    /// `key` is zeroized on failure.
+    pub fn unwrap_key(&self, ad: &[u8], wrapped: &[u8], key: &mut [u8]) -> aead::Result<()> {
+        if wrapped.len() != key.len() + Self::OVERHEAD {
+            return Err(Error);
+        }
+        let (siv, ciphertext) = wrapped.split_at(Self::OVERHEAD);
+        key.copy_from_slice(ciphertext);
+        let nonce = Nonce::<Self>::default();
+        let result = self.decrypt_in_place_detached(&nonce, ad, key, siv.into());
+        if result.is_err() {
+            key.zeroize();
+        }
+        result
+    }
+}
+
+impl KeySizeUser for Zuc256Siv {
+    type KeySize = U32;
+}
+
+impl KeyInit for Zuc256Siv {
+    fn new(key: &Key<Self>) -> Self {
+        let key = (*key).into();
+        let (ke, km) = derive_keys(&key, &layout_iv(&[0; 16], DOMAIN_KDF, SIV_SIZE));
+        let (kp, mut unused) = derive_keys(&key, &layout_iv(&[0; 16], DOMAIN_KDF_PRF, SIV_SIZE));
+        unused.zeroize();
+        Self { ke, km, kp }
+    }
+}
+
+impl AeadCore for Zuc256Siv {
+    type NonceSize = U16;
+    type TagSize = U16;
+    type CiphertextOverhead = U0;
+}
+
+impl AeadInPlace for Zuc256Siv {
+    fn encrypt_in_place_detached(
+        &self,
+        nonce: &Nonce<Self>,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+    ) -> aead::Result<Tag<Self>> {
+        let siv = self.siv(nonce, associated_data, buffer);
+        self.apply_keystream(&siv, buffer);
+        Ok(siv)
+    }
+
+    fn decrypt_in_place_detached(
+        &self,
+        nonce: &Nonce<Self>,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+        tag: &Tag<Self>,
+    ) -> aead::Result<()> {
+        self.apply_keystream(tag, buffer);
+        let expected = self.siv(nonce, associated_data, buffer);
+        if !bool::from(expected.ct_eq(tag)) {
+            self.apply_keystream(tag, buffer);
+            return Err(Error);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Zuc256Siv {
+    fn drop(&mut self) {
+        self.ke.zeroize();
+        self.km.zeroize();
+        self.kp.zeroize();
+    }
+}
+
+impl fmt::Debug for Zuc256Siv {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Zuc256Siv").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use const_str::hex;
+
+    /// key wrap vector generated by this crate
+    struct WrapVector {
+        kek: [u8; 32],
+        ad: &'static [u8],
+        key: &'static [u8],
+        wrapped: &'static [u8],
+    }
+
+    static WRAP_VECTORS: &[WrapVector] = &[
+        WrapVector {
+            kek: hex!("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f"),
+            ad: b"",
+            key: &hex!("00112233445566778899aabbccddeeff"),
+            wrapped: &hex!("da3ba6e53e7338864301f3d91d8aeb205f774398760ade8e7971ed81ea29c1e2"),
+        },
+        WrapVector {
+            kek: hex!("fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0efeeedecebeae9e8e7e6e5e4e3e2e1e0"),
+            ad: b"IMSI 001010123456789",
+            key: &hex!("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f"),
+            wrapped: &hex!("b639b5477d80b5095d8d8dddd5be096a9bededd46b8dd6955e1bf881347f492e411ae925e148ffc21af4e9b4e6cd9399"),
+        },
+        WrapVector {
+            kek: [0x5a; 32],
+            ad: &[0xa5; 40],
+            key: &hex!("000b16212c37424d58636e79848f9aa5b0bbc6d1dce7f2fd"),
+            wrapped: &hex!("62a03c0a32aa0209c339bf03cdf45a7cfc7f57563060a80b6a1d3854e53403e3de9ea6bc00f1e3da"),
+        },
+    ];
+
+    #[test]
+    fn vectors() {
+        for x in WRAP_VECTORS {
+            let (ad, key, wrapped) = (x.ad, x.key, x.wrapped);
+            let siv = Zuc256Siv::new(&x.kek.into());
+
+            let mut out = [0; 64];
+            let out = &mut out[..wrapped.len()];
+            siv.wrap_key(ad, key, out).unwrap();
+            assert_eq!(out, wrapped);
+
+            let mut unwrapped = [0; 48];
+            let unwrapped = &mut unwrapped[..key.len()];
+            siv.unwrap_key(ad, wrapped, unwrapped).unwrap();
+            assert_eq!(unwrapped, key);
+        }
+    }
+
+    #[test]
+    fn deterministic() {
+        let siv = Zuc256Siv::new(&[0x13; 32].into());
+        let nonce = [0x8e; 16].into();
+        let msg = *b"deterministic authenticated encryption";
+
+        let mut a = msg;
+        let mut b = msg;
+        let ta = siv
+            .encrypt_in_place_detached(&nonce, b"ad", &mut a)
+            .unwrap();
+        let tb = siv
+            .encrypt_in_place_detached(&nonce, b"ad", &mut b)
+            .unwrap();
+        assert_eq!((a, ta), (b, tb));
+        assert_ne!(a, msg);
+
+        let mut c = msg;
+        let tc = siv
+            .encrypt_in_place_detached(&[0x8f; 16].into(), b"ad", &mut c)
+            .unwrap();
+        assert_ne!((a, ta), (c, tc));
+
+        let mut d = msg;
+        let td = siv
+            .encrypt_in_place_detached(&nonce, b"ae", &mut d)
+            .unwrap();
+        assert_ne!((a, ta), (d, td));
+
+        siv.decrypt_in_place_detached(&nonce, b"ad", &mut a, &ta)
+            .unwrap();
+        assert_eq!(a, msg);
+    }
+
+    #[test]
+    fn not_affine() {
+        // the MAC alone gives S(P1) ^ S(P2) ^ S(P3) == S(P1 ^ P2 ^ P3)
+        let siv = Zuc256Siv::new(&[0x39; 32].into());
+        let nonce = Nonce::<Zuc256Siv>::default();
+        let p: [[u8; 16]; 3] = [[0x01; 16], [0x5c; 16], [0xe7; 16]];
+        let sum: [u8; 16] = core::array::from_fn(|i| p[0][i] ^ p[1][i] ^ p[2][i]);
+
+        let mut tags = [[0; 16]; 4];
+        for (tag, msg) in tags.iter_mut().zip(p.iter().chain([&sum])) {
+            let mut buffer = *msg;
+            let t = siv.encrypt_in_place_detached(&nonce, b"ad", &mut buffer);
+            tag.copy_from_slice(&t.unwrap());
+        }
+        let combined: [u8; 16] = core::array::from_fn(|i| tags[0][i] ^ tags[1][i] ^ tags[2][i]);
+        assert_ne!(combined, tags[3]);
+    }
+
+    #[test]
+    fn tamper() {
+        let siv = Zuc256Siv::new(&[0x26; 32].into());
+        let key = hex!("00112233445566778899aabbccddeeff");
+
+        let mut wrapped = [0; 32];
+        siv.wrap_key(b"ad", &key, &mut wrapped).unwrap();
+
+        let mut out = [0xff; 16];
+        assert!(siv.unwrap_key(b"ae", &wrapped, &mut out).is_err());
+        assert_eq!(out, [0; 16]);
+
+        for i in [0, 15, 16, 31] {
+            let mut forged = wrapped;
+            forged[i] ^= 0x01;
+            assert!(siv.unwrap_key(b"ad", &forged, &mut out).is_err());
+        }
+
+        assert!(siv.wrap_key(b"ad", &key, &mut [0; 31]).is_err());
+        assert!(siv.unwrap_key(b"ad", &wrapped, &mut [0; 15]).is_err());
+
+        let (tag, ciphertext) = wrapped.split_at(16);
+        let mut buffer: [u8; 16] = ciphertext.try_into().unwrap();
+        let mut forged: [u8; 16] = tag.try_into().unwrap();
+        forged[3] ^= 0x40;
+        let nonce = Nonce::<Zuc256Siv>::default();
+        let result = siv.decrypt_in_place_detached(&nonce, b"ad", &mut buffer, &forged.into());
+        assert!(result.is_err());
+        assert_eq!(buffer, ciphertext);
+    }
+}
@@ -0,0 +1,312 @@
+use super::aead::Zuc256Aead;
+
+use crate::internal::mac::MacTag;
+
+use core::fmt;
+
+use aead::consts::U5;
+use aead::generic_array::typenum::Unsigned;
+use aead::stream::{Decryptor, Encryptor, NewStream, Nonce as StreamNonce, StreamPrimitive};
+use aead::{AeadCore, AeadInPlace, Buffer, Error, Nonce, Tag};
+
+/// Chunked AEAD on ZUC256 for large messages
+/// ([STREAM](https://eprint.iacr.org/2015/189.pdf))
+///
+/// A message is split into chunks, and each chunk is encrypted by [`Zuc256Aead<T>`]
+/// with its own nonce and tag:
+///
+/// | bytes    | value                                       |
+/// | -------- | ------------------------------------------- |
+/// | 0 .. 11  | base nonce of the message                   |
+/// | 11 .. 15 | chunk index, 32-bit big endian              |
+/// | 15       | `0x01` for the last chunk, `0x00` otherwise |
+///
+/// A reordered chunk fails to decrypt at its new index, and a truncated message is detected
+/// because its new last chunk was not encrypted as the last one.
+/// Each chunk can be decrypted on its own, given its index and whether it is the last one.
+///
+/// [`Zuc256StreamEncryptor`] and [`Zuc256StreamDecryptor`] process the chunks in order.
+///
+/// # Examples
+/// ```
+/// use zuc::aead::stream::NewStream;
+/// use zuc::aead::KeyInit;
+/// use zuc::zuc256::{Zuc256Aead128, Zuc256StreamAead};
+///
+/// let aead = Zuc256Aead128::new(&[0x42; 32].into());
+/// let stream = Zuc256StreamAead::from_aead(aead, &[0x07; 11].into());
+///
+/// let mut chunks = [*b"chunk 0", *b"chunk 1", *b"chunk 2"];
+/// let mut tags = Vec::new();
+/// for (i, chunk) in chunks.iter_mut().enumerate() {
+///     let last = i == 2;
+///     tags.push(stream.encrypt_chunk(i as u32, last, b"", chunk).unwrap());
+/// }
+///
+/// // random access
+/// stream.decrypt_chunk(1, false, b"", &mut chunks[1], &tags[1]).unwrap();
+/// assert_eq!(&chunks[1], b"chunk 1");
+///
+/// // truncation
+/// assert!(stream.decrypt_chunk(0, true, b"", &mut chunks[0], &tags[0]).is_err());
+/// ```
+#[derive(Clone)]
+pub struct Zuc256StreamAead<T: MacTag> {
+    /// chunk cipher
+    aead: Zuc256Aead<T>,
+    /// base nonce
+    nonce: StreamNonce<Zuc256Aead<T>, Self>,
+}
+
+/// Encryptor of [`Zuc256StreamAead`] for chunks in order
+pub type Zuc256StreamEncryptor<T> = Encryptor<Zuc256Aead<T>, Zuc256StreamAead<T>>;
+
+/// Decryptor of [`Zuc256StreamAead`] for chunks in order
+pub type Zuc256StreamDecryptor<T> = Decryptor<Zuc256Aead<T>, Zuc256StreamAead<T>>;
+
+impl<T: MacTag> Zuc256StreamAead<T> {
+    /// Computes the nonce of a chunk
+    fn chunk_nonce(&self, index: u32, last: bool) -> Nonce<Zuc256Aead<T>> {
+        let mut nonce = Nonce::<Zuc256Aead<T>>::default();
+        nonce[..11].copy_from_slice(&self.nonce);
+        nonce[11..15].copy_from_slice(&index.to_be_bytes());
+        nonce[15] = u8::from(last);
+        nonce
+    }
+
+    /// Encrypts the chunk at `index` in place and returns its tag
+    ///
+    /// # Errors
+    /// Returns an error if the encryption fails.
+    pub fn encrypt_chunk(
+        &self,
+        index: u32,
+        last: bool,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+    ) -> aead::Result<Tag<Zuc256Aead<T>>> {
+        let nonce = self.chunk_nonce(index, last);
+        self.aead
+            .encrypt_in_place_detached(&nonce, associated_data, buffer)
+    }
+
+    /// Decrypts the chunk at `index` in place
+    ///
+    /// # Errors
+    /// Returns an error if the chunk is not authentic at `index` with the `last` flag.
+    /// The buffer is left untouched in that case.
+    pub fn decrypt_chunk(
+        &self,
+        index: u32,
+        last: bool,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+        tag: &Tag<Zuc256Aead<T>>,
+    ) -> aead::Result<()> {
+        let nonce = self.chunk_nonce(index, last);
+        self.aead
+            .decrypt_in_place_detached(&nonce, associated_data, buffer, tag)
+    }
+}
+
+impl<T: MacTag> NewStream<Zuc256Aead<T>> for Zuc256StreamAead<T> {
+    fn from_aead(aead: Zuc256Aead<T>, nonce: &StreamNonce<Zuc256Aead<T>, Self>) -> Self {
+        Self {
+            aead,
+            nonce: *nonce,
+        }
+    }
+}
+
+impl<T: MacTag> StreamPrimitive<Zuc256Aead<T>> for Zuc256StreamAead<T> {
+    type NonceOverhead = U5;
+    type Counter = u32;
+    const COUNTER_INCR: u32 = 1;
+    const COUNTER_MAX: u32 = u32::MAX;
+
+    fn encrypt_in_place(
+        &self,
+        position: u32,
+        last_block: bool,
+        associated_data: &[u8],
+        buffer: &mut dyn Buffer,
+    ) -> aead::Result<()> {
+        let tag = self.encrypt_chunk(position, last_block, associated_data, buffer.as_mut())?;
+        buffer.extend_from_slice(&tag)
+    }
+
+    fn decrypt_in_place(
+        &self,
+        position: u32,
+        last_block: bool,
+        associated_data: &[u8],
+        buffer: &mut dyn Buffer,
+    ) -> aead::Result<()> {
+        let tag_size = <Zuc256Aead<T> as AeadCore>::TagSize::USIZE;
+        let Some(len) = buffer.len().checked_sub(tag_size) else {
+            return Err(Error);
+        };
+
+        let (chunk, tag) = buffer.as_mut().split_at_mut(len);
+        let tag = Tag::<Zuc256Aead<T>>::clone_from_slice(tag);
+        self.decrypt_chunk(position, last_block, associated_data, chunk, &tag)?;
+        buffer.truncate(len);
+        Ok(())
+    }
+}
+
+impl<T: MacTag> fmt::Debug for Zuc256StreamAead<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Zuc256StreamAead")
+            .field("nonce", &self.nonce)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::zuc256::Zuc256Aead64;
+
+    use aead::KeyInit;
+    use numeric_cast::TruncatingCast;
+
+    const CHUNK: usize = 64;
+    const CHUNKS: usize = 5;
+
+    const MSG_LEN: usize = CHUNK * CHUNKS - 10;
+
+    fn message() -> [u8; MSG_LEN] {
+        core::array::from_fn(|i| (i * 7 + 3).truncating_cast::<u8>())
+    }
+
+    fn stream() -> Zuc256StreamAead<u64> {
+        let aead = Zuc256Aead64::new(&[0x2d; 32].into());
+        Zuc256StreamAead::from_aead(aead, &[0xc4; 11].into())
+    }
+
+    /// encrypts the message and returns the ciphertext and the tags
+    fn seal(
+        stream: &Zuc256StreamAead<u64>,
+        ad: &[u8],
+    ) -> ([u8; MSG_LEN], [Tag<Zuc256Aead64>; CHUNKS]) {
+        let mut ciphertext = message();
+        let mut tags = [Tag::<Zuc256Aead64>::default(); CHUNKS];
+        for (i, chunk) in (0..).zip(ciphertext.chunks_mut(CHUNK)) {
+            let last = i == CHUNKS - 1;
+            tags[i] = stream
+                .encrypt_chunk(i.truncating_cast::<u32>(), last, ad, chunk)
+                .unwrap();
+        }
+        (ciphertext, tags)
+    }
+
+    #[test]
+    fn chunks() {
+        let stream = stream();
+        let msg = message();
+        let (ciphertext, tags) = seal(&stream, b"ad");
+
+        // each chunk is the AEAD of its own nonce
+        let aead = Zuc256Aead64::new(&[0x2d; 32].into());
+        let mut nonce = [0xc4; 16];
+        nonce[11..].copy_from_slice(&[0, 0, 0, 4, 1]);
+        let mut last = [0; MSG_LEN - CHUNK * 4];
+        last.copy_from_slice(&msg[CHUNK * 4..]);
+        let tag = aead
+            .encrypt_in_place_detached(&nonce.into(), b"ad", &mut last)
+            .unwrap();
+        assert_eq!((&last[..], tag), (&ciphertext[CHUNK * 4..], tags[4]));
+
+        // random access in any order
+        for i in [3_u32, 0, 4, 2, 1] {
+            let start = i as usize * CHUNK;
+            let range = start..MSG_LEN.min(start + CHUNK);
+            let mut buf = [0; CHUNK];
+            let chunk = &mut buf[..range.len()];
+            chunk.copy_from_slice(&ciphertext[range.clone()]);
+            let last = i as usize == CHUNKS - 1;
+            stream
+                .decrypt_chunk(i, last, b"ad", chunk, &tags[i as usize])
+                .unwrap();
+            assert_eq!(chunk, &msg[range]);
+        }
+    }
+
+    #[test]
+    fn reorder_and_truncate() {
+        let stream = stream();
+        let (ciphertext, tags) = seal(&stream, b"");
+        let chunk = |i: usize| {
+            let mut buf = [0; CHUNK];
+            buf.copy_from_slice(&ciphertext[i * CHUNK..(i + 1) * CHUNK]);
+            buf
+        };
+
+        // reordered
+        let mut buf = chunk(1);
+        assert!(stream
+            .decrypt_chunk(2, false, b"", &mut buf, &tags[1])
+            .is_err());
+        assert_eq!(buf, chunk(1));
+
+        // truncated after chunk 2
+        let mut buf = chunk(2);
+        assert!(stream
+            .decrypt_chunk(2, true, b"", &mut buf, &tags[2])
+            .is_err());
+
+        // last chunk presented as a middle one
+        let mut buf = [0; CHUNK - 10];
+        buf.copy_from_slice(&ciphertext[4 * CHUNK..]);
+        assert!(stream
+            .decrypt_chunk(4, false, b"", &mut buf, &tags[4])
+            .is_err());
+
+        // another base nonce
+        let aead = Zuc256Aead64::new(&[0x2d; 32].into());
+        let other = Zuc256StreamAead::from_aead(aead, &[0xc5; 11].into());
+        let mut buf = chunk(0);
+        assert!(other
+            .decrypt_chunk(0, false, b"", &mut buf, &tags[0])
+            .is_err());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn encryptor() {
+        use alloc::vec::Vec;
+
+        let key = [0x2d; 32].into();
+        let nonce = [0xc4; 11].into();
+        let msg = message();
+
+        let mut encryptor = Zuc256StreamEncryptor::<u64>::new(&key, &nonce);
+        let mut sealed: Vec<Vec<u8>> = Vec::new();
+        let mut chunks = msg.chunks(CHUNK);
+        for chunk in chunks.by_ref().take(CHUNKS - 1) {
+            sealed.push(encryptor.encrypt_next(chunk).unwrap());
+        }
+        sealed.push(encryptor.encrypt_last(chunks.next().unwrap()).unwrap());
+
+        let (ciphertext, tags) = seal(&stream(), b"");
+        for i in 0..CHUNKS {
+            let start = i * CHUNK;
+            let end = MSG_LEN.min(start + CHUNK);
+            assert_eq!(sealed[i][..end - start], ciphertext[start..end]);
+            assert_eq!(sealed[i][end - start..], tags[i][..]);
+        }
+
+        let mut decryptor = Zuc256StreamDecryptor::<u64>::new(&key, &nonce);
+        let mut plaintext = Vec::new();
+        for sealed in &sealed[..CHUNKS - 1] {
+            plaintext.extend(decryptor.decrypt_next(&sealed[..]).unwrap());
+        }
+        plaintext.extend(decryptor.decrypt_last(&sealed[CHUNKS - 1][..]).unwrap());
+        assert_eq!(plaintext, msg);
+
+        let mut decryptor = Zuc256StreamDecryptor::<u64>::new(&key, &nonce);
+        assert!(decryptor.decrypt_next(&sealed[1][..]).is_err());
+    }
+}
@@ -1,5 +1,32 @@
 use super::Zuc256Keystream;
 
+use crate::internal::seek::{impl_seekable, Checkpoints};
+
 /// ZUC256 stream cipher
 /// ([ZUC256-version1.1](http://www.is.cas.cn/ztzl2016/zouchongzhi/201801/W020180416526664982687.pdf))
 pub type Zuc256StreamCipher = cipher::StreamCipherCoreWrapper<Zuc256Keystream>;
+
+/// Seekable ZUC256 stream cipher
+/// ([ZUC256-version1.1](http://www.is.cas.cn/ztzl2016/zouchongzhi/201801/W020180416526664982687.pdf))
+///
+/// It implements [`StreamCipherSeek`](cipher::StreamCipherSeek) with [`Zuc256SeekableKeystream`].
+pub type Zuc256SeekableStreamCipher = cipher::StreamCipherCoreWrapper<Zuc256SeekableKeystream>;
+
+/// Seekable ZUC256 keystream generator, the core of [`Zuc256SeekableStreamCipher`]
+///
+/// See [`Zuc128SeekableKeystream`](crate::zuc128::Zuc128SeekableKeystream) for how seeking works.
+#[derive(Debug, Clone)]
+pub struct Zuc256SeekableKeystream {
+    /// keystream generator
+    zuc: Zuc256Keystream,
+    /// states to seek from
+    checkpoints: Checkpoints,
+}
+
+impl_seekable!(Zuc256SeekableKeystream, Zuc256Keystream);
+
+impl cipher::KeyIvInit for Zuc256SeekableKeystream {
+    fn new(key: &cipher::Key<Self>, iv: &cipher::Iv<Self>) -> Self {
+        Self::new(Zuc256Keystream::new(key.as_ref(), iv.as_ref()))
+    }
+}